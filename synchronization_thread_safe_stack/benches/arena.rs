@@ -0,0 +1,31 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use synchronization_thread_safe_stack::{ArenaStack, Stack};
+
+const SIZES: [u64; 3] = [1_000, 100_000, 1_000_000];
+
+// Measures how fast iter() walks every element of the Box-based Stack compared with the arena-backed
+// ArenaStack, whose nodes sit next to each other in one Vec. Both are built before timing starts;
+// only the traversal is measured.
+fn iter_traversal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter");
+    for size in SIZES {
+        group.throughput(Throughput::Elements(size));
+        group.bench_with_input(BenchmarkId::new("boxed", size), &size, |b, &size| {
+            let stack: Stack<u64> = (0..size).collect();
+            b.iter(|| black_box(stack.iter().sum::<u64>()));
+        });
+        group.bench_with_input(BenchmarkId::new("arena", size), &size, |b, &size| {
+            let mut stack = ArenaStack::new();
+            for i in 0..size {
+                stack.push(i);
+            }
+            b.iter(|| black_box(stack.iter().sum::<u64>()));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, iter_traversal);
+criterion_main!(benches);
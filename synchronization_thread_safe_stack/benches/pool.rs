@@ -0,0 +1,67 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use synchronization_thread_safe_stack::Stack;
+
+// Counts every allocation so the benchmark can report how many allocations the pool saves.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Elements pushed and then popped per round of churn.
+const BATCH: u64 = 64;
+const ROUNDS: u64 = 1_000;
+
+// Heavy push/pop churn: fill the stack with a batch, then empty it again, ROUNDS times.
+fn churn(stack: &mut Stack<u64>) {
+    for _ in 0..ROUNDS {
+        for i in 0..BATCH {
+            stack.push(i);
+        }
+        while let Some(value) = stack.pop() {
+            black_box(value);
+        }
+    }
+}
+
+// Number of allocations one churn() call performs on a stack with the given pool size.
+fn allocations_for(max_free: usize) -> usize {
+    let mut stack = Stack::with_pool(max_free);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    churn(&mut stack);
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+// Compares a plain Stack (one allocation per push) with a pooled one whose pool fits a whole batch,
+// printing the allocation counts and then timing the churn.
+fn node_pool(c: &mut Criterion) {
+    for max_free in [0, BATCH as usize] {
+        println!("max_free = {:>2}: {} allocations for {} pushes", max_free, allocations_for(max_free), BATCH * ROUNDS);
+    }
+    let mut group = c.benchmark_group("churn");
+    for max_free in [0, BATCH as usize] {
+        group.bench_with_input(BenchmarkId::new("max_free", max_free), &max_free, |b, &max_free| {
+            let mut stack = Stack::with_pool(max_free);
+            b.iter(|| churn(&mut stack));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, node_pool);
+criterion_main!(benches);
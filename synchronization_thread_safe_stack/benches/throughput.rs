@@ -0,0 +1,63 @@
+use std::hint::black_box;
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use synchronization_thread_safe_stack::{ConcurrentStack, LockFreeStack, ShardedStack};
+
+// Push/pop pairs each thread performs per benchmark iteration.
+const PAIRS_PER_THREAD: u64 = 10_000;
+const THREAD_COUNTS: [u64; 4] = [1, 2, 4, 8];
+
+// Run 'threads' threads that each call 'pair' PAIRS_PER_THREAD times, and wait for all of them.
+fn run_threads(threads: u64, pair: impl Fn(u64) + Sync) {
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for i in 0..PAIRS_PER_THREAD {
+                    pair(i);
+                }
+            });
+        }
+    });
+}
+
+// Measures push/pop throughput for the Mutex-based ConcurrentStack, the ShardedStack (8 Mutexes) and the
+// lock-free stack under 1, 2, 4 and 8 threads. Throughput is reported per operation (a push and a pop count as two),
+// so the results read as operations per second. Nothing is written to files.
+fn push_pop_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_pop");
+    for threads in THREAD_COUNTS {
+        group.throughput(Throughput::Elements(threads * PAIRS_PER_THREAD * 2));
+        group.bench_with_input(BenchmarkId::new("mutex", threads), &threads, |b, &threads| {
+            let stack = ConcurrentStack::new();
+            b.iter(|| {
+                run_threads(threads, |i| {
+                    stack.push(i).unwrap();
+                    black_box(stack.pop().unwrap());
+                })
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("sharded", threads), &threads, |b, &threads| {
+            let stack = ShardedStack::<u64, 8>::new();
+            b.iter(|| {
+                run_threads(threads, |i| {
+                    stack.push(i).unwrap();
+                    black_box(stack.pop().unwrap());
+                })
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("lock_free", threads), &threads, |b, &threads| {
+            let stack = LockFreeStack::new();
+            b.iter(|| {
+                run_threads(threads, |i| {
+                    stack.push(i);
+                    black_box(stack.pop());
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, push_pop_throughput);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+// A fast producer feeding a handful of slower consumers through a bounded BlockingStack. Once the stack is full
+// the producer has to wait in push() for a consumer to pop, so at most 'CAPACITY' items are ever waiting.
+// Run with: cargo run --release --example producer_consumer
+
+use synchronization_thread_safe_stack::demo::producer_consumer;
+
+const ITEMS: u64 = 1_000_000;
+const CONSUMERS: usize = 4;
+const CAPACITY: usize = 64;
+
+fn main() {
+    let report = producer_consumer(ITEMS, CONSUMERS, CAPACITY);
+    assert_eq!(report.sum, ITEMS * (ITEMS + 1) / 2, "every pushed item should be popped exactly once");
+    for (index, count) in report.consumed.iter().enumerate() {
+        println!("Consumer {} popped {} items", index, count);
+    }
+    let seconds = report.elapsed.as_secs_f64();
+    println!("{} items through a stack of capacity {} in {:.3}s ({:.0} items/s)", ITEMS, CAPACITY, seconds, ITEMS as f64 / seconds);
+}
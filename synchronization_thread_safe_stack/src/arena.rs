@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+
+// A stack whose nodes live side by side in one Vec, linked by index instead of by Box.
+// Walking the chain then reads consecutive memory rather than chasing pointers to allocations scattered
+// across the heap, which is kinder to the CPU cache, and pushing only allocates when the Vec has to grow.
+// Because a stack only ever removes the node it added last, the top node is always the last slot in the Vec:
+// pop is a Vec::pop and the arena never develops holes that would need compacting.
+pub struct ArenaStack<T> {
+    nodes: Vec<ArenaNode<T>>,
+    // Index of the top node, or None when the stack is empty.
+    top: Option<usize>,
+}
+
+struct ArenaNode<T> {
+    data: T,
+    // Index of the node below this one.
+    next: Option<usize>,
+}
+
+impl<T> ArenaStack<T> {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new(), top: None }
+    }
+    // Create a stack with room for 'capacity' nodes before the arena needs to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { nodes: Vec::with_capacity(capacity), top: None }
+    }
+    // Make room for at least 'additional' more nodes, so that many pushes don't have to grow the arena.
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+    pub fn push(&mut self, data: T) {
+        // The new node links to the current top and takes the next free slot.
+        self.nodes.push(ArenaNode { data, next: self.top });
+        self.top = Some(self.nodes.len() - 1);
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        // The top node is always the last slot, so removing it leaves no gap.
+        let node = self.nodes.pop()?;
+        self.top = node.next;
+        Some(node.data)
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.top.map(|index| &self.nodes[index].data)
+    }
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.top.map(|index| &mut self.nodes[index].data)
+    }
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.top.is_none()
+    }
+    // Visit the elements top first by following the 'next' indices, like Stack::iter() follows its pointers.
+    pub fn iter(&self) -> ArenaIter<'_, T> {
+        ArenaIter { nodes: &self.nodes, next: self.top }
+    }
+}
+
+impl<T> Default for ArenaStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A borrowing iterator over an ArenaStack, created by calling iter().
+pub struct ArenaIter<'a, T> {
+    nodes: &'a [ArenaNode<T>],
+    next: Option<usize>,
+}
+
+impl<'a, T> Iterator for ArenaIter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|index| {
+            let node = &self.nodes[index];
+            self.next = node.next;
+            &node.data
+        })
+    }
+}
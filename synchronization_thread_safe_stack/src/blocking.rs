@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::BoundedStack;
+
+// A Stack for producer/consumer workloads: consumers calling pop_wait() sleep on a Condvar
+// until a producer pushes, instead of busy-looping on a pop() that keeps returning None.
+// Once the producers are done, close() lets the consumers drain what is left and then stop waiting.
+// A stack created with bounded() also works the other way round: once it is full, producers calling push()
+// sleep until a consumer pops, so a fast producer is held back instead of piling up elements without limit.
+pub struct BlockingStack<T> {
+    // new() gives it a capacity of usize::MAX, which is never reached, so only bounded() stacks ever fill up.
+    inner: Mutex<BoundedStack<T>>,
+    // Signalled every time an element is pushed, and for every waiter when the stack is closed.
+    not_empty: Condvar,
+    // Signalled every time an element is popped, for producers waiting on a full stack.
+    not_full: Condvar,
+    // Set by close(). Only written while holding 'inner', so a consumer that saw it unset under the lock
+    // is already waiting on the Condvar by the time close() notifies it.
+    closed: AtomicBool,
+}
+
+impl<T> BlockingStack<T> {
+    pub fn new() -> Self {
+        Self::with_inner(BoundedStack::new(usize::MAX))
+    }
+    // Create a stack that holds at most 'capacity' elements; push() waits for room once it is full.
+    pub fn bounded(capacity: usize) -> Self {
+        assert!(capacity > 0, "a bounded BlockingStack needs room for at least one element");
+        Self::with_inner(BoundedStack::new(capacity))
+    }
+    fn with_inner(stack: BoundedStack<T>) -> Self {
+        Self {
+            inner: Mutex::new(stack),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+    // Safe to call from any number of producers while any number of consumers wait in pop_wait().
+    // On a full bounded() stack this waits until a consumer has made room.
+    pub fn push(&self, data: T) {
+        drop(self.push_locked(self.lock(), data));
+        // One element was added, so exactly one waiting consumer can make progress. notify_one is enough even with
+        // many consumers: a consumer only sleeps after seeing the stack empty under the lock, so it can't miss an
+        // element pushed before it started waiting, and if a woken consumer loses the element to another consumer
+        // that was never asleep, that element was still delivered exactly once and the loser just waits again.
+        self.not_empty.notify_one();
+    }
+    // Push several items under one lock acquisition, in order, so the last one ends up on top. If a bounded()
+    // stack fills up part-way through, the rest of the batch waits for room just as push() would.
+    pub fn push_batch(&self, items: Vec<T>) {
+        let mut stack = self.lock();
+        for data in items {
+            stack = self.push_locked(stack, data);
+            // One wakeup per element, as the same pushes made one at a time would send. Waking every consumer
+            // with notify_all instead would have all but one of them per element find the stack empty and go back
+            // to sleep. They are sent straight away rather than after the batch, since if the stack fills up
+            // the consumers have to be awake to make room for the rest.
+            self.not_empty.notify_one();
+        }
+    }
+    // Push onto the locked stack, waiting for a consumer to make room first if it is full. Returns the guard,
+    // which wait() may have had to give up and re-acquire in between.
+    fn push_locked<'a>(
+        &'a self,
+        mut stack: MutexGuard<'a, BoundedStack<T>>,
+        mut data: T,
+    ) -> MutexGuard<'a, BoundedStack<T>> {
+        while let Err(rejected) = stack.push(data) {
+            data = rejected;
+            stack = self.not_full.wait(stack).unwrap_or_else(PoisonError::into_inner);
+        }
+        stack
+    }
+    // Wake one producer waiting for room, after an element was popped. Like not_empty, one element of room
+    // lets exactly one waiting producer make progress.
+    fn popped(&self, data: T) -> T {
+        self.not_full.notify_one();
+        data
+    }
+    // Wait for an element and pop it. Returns None only once the stack has been closed and is empty,
+    // which tells a consumer there is nothing more to come and its thread can finish.
+    pub fn pop_wait(&self) -> Option<T> {
+        let mut stack = self.lock();
+        loop {
+            if let Some(data) = stack.pop() {
+                return Some(self.popped(data));
+            }
+            if self.is_closed() {
+                return None;
+            }
+            // wait() releases the lock while sleeping and re-acquires it before returning.
+            // Wakeups can be spurious (or another consumer got there first), so loop and check again.
+            stack = self.not_empty.wait(stack).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+    // Like pop_wait(), but gives up and returns None if nothing arrives within 'dur', so a consumer
+    // isn't stuck forever when producers stall. It also returns None straight away once the stack is closed and empty.
+    pub fn pop_timeout(&self, dur: Duration) -> Option<T> {
+        // Wait against a fixed deadline: after a spurious wakeup (or losing an element to another consumer)
+        // only the time that is left is waited for, rather than the full duration again.
+        // A duration too long to add to the current time (e.g. Duration::MAX) never runs out, so it is pop_wait().
+        let deadline = match Instant::now().checked_add(dur) {
+            Some(deadline) => deadline,
+            None => return self.pop_wait(),
+        };
+        let mut stack = self.lock();
+        loop {
+            if let Some(data) = stack.pop() {
+                return Some(self.popped(data));
+            }
+            if self.is_closed() {
+                return None;
+            }
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            stack = self.not_empty.wait_timeout(stack, remaining).unwrap_or_else(PoisonError::into_inner).0;
+        }
+    }
+    // Tell every consumer that no more elements are coming. Consumers keep popping whatever is still on the
+    // stack, and once it is empty pop_wait() and pop_timeout() return None instead of waiting.
+    // Closing doesn't stop pushes; anything pushed afterwards is still handed out to callers that pop.
+    pub fn close(&self) {
+        let _stack = self.lock();
+        self.closed.store(true, Ordering::Relaxed);
+        // Every waiter has to see the flag, not just one.
+        self.not_empty.notify_all();
+    }
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+    // The Stack's own methods never leave it half-modified, so a lock poisoned by a panicking
+    // thread is recovered rather than propagated to every other producer and consumer.
+    fn lock(&self) -> MutexGuard<'_, BoundedStack<T>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Default for BlockingStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,48 @@
+use crate::Stack;
+#[cfg(feature = "std")]
+use crate::StackError;
+
+// A Stack that holds at most 'capacity' elements. Instead of growing without limit,
+// push hands the value back once the stack is full, so producers can apply backpressure.
+pub struct BoundedStack<T> {
+    stack: Stack<T>,
+    capacity: usize,
+}
+
+impl<T> BoundedStack<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self { stack: Stack::new(), capacity }
+    }
+    // Push the value, or give it back as Err if the stack is already at capacity.
+    pub fn push(&mut self, data: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(data);
+        }
+        self.stack.push(data);
+        Ok(())
+    }
+    // push() for callers that would rather propagate a full stack with ? as StackError::CapacityExceeded.
+    // The rejected value is dropped, so use push() when it needs to be kept (e.g. to retry later).
+    #[cfg(feature = "std")]
+    pub fn push_checked(&mut self, data: T) -> Result<(), StackError> {
+        self.push(data).map_err(|_| StackError::CapacityExceeded)
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        self.stack.pop()
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.stack.peek()
+    }
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn is_full(&self) -> bool {
+        self.stack.len() >= self.capacity
+    }
+}
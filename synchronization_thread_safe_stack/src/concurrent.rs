@@ -0,0 +1,181 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, PoisonError, TryLockError};
+use std::thread;
+// Under the loom model checker (RUSTFLAGS="--cfg loom") the Mutex is swapped for loom's, which lets
+// tests/loom.rs explore every interleaving of the lock operations. Normal builds use std's.
+#[cfg(loom)]
+use loom::sync::{Mutex, MutexGuard};
+#[cfg(not(loom))]
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{Stack, StackError};
+
+// The error lock() returns when another thread panicked while it held the stack's lock.
+// It carries the lock guard, so the caller can decide the stack is still usable and
+// get at it with into_inner() instead of the panic cascading into every other thread.
+// The other methods report the same situation as StackError::Poisoned.
+pub type PoisonedStack<'a, T> = PoisonError<MutexGuard<'a, Stack<T>>>;
+
+// A Stack that can be shared between threads (usually inside an Arc) without the caller
+// locking anything: every method takes &self and acquires the internal Mutex itself.
+pub struct ConcurrentStack<T> {
+    inner: Mutex<Stack<T>>,
+    // How many pop(), pop_with_len() and try_pop() calls found an element (hits) or an empty stack (misses);
+    // see stats().
+    pop_hits: AtomicU64,
+    pop_misses: AtomicU64,
+}
+
+impl<T> ConcurrentStack<T> {
+    pub fn new() -> Self {
+        Self { inner: Mutex::new(Stack::new()), pop_hits: AtomicU64::new(0), pop_misses: AtomicU64::new(0) }
+    }
+    // Create an empty stack that is ready to be shared: clone the Arc once per thread.
+    pub fn new_shared() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+    pub fn push(&self, data: T) -> Result<(), StackError> {
+        // The ? turns a poisoned lock into StackError::Poisoned for the caller instead of unwrapping it.
+        let mut stack = self.inner.lock()?;
+        stack.push(data);
+        Ok(())
+    }
+    // Push a whole batch under a single lock acquisition rather than locking once per element.
+    // Other threads can't interleave their pushes with the batch.
+    pub fn push_iter<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), StackError> {
+        let mut stack = self.inner.lock()?;
+        stack.push_iter(iter);
+        Ok(())
+    }
+    // The Vec form of push_iter(), for producers that build up their items before handing them over.
+    // The items are pushed in order, exactly as the same number of push() calls would, so the last one ends up on top.
+    pub fn push_batch(&self, items: Vec<T>) -> Result<(), StackError> {
+        self.push_iter(items)
+    }
+    pub fn pop(&self) -> Result<Option<T>, StackError> {
+        let mut stack = self.inner.lock()?;
+        Ok(self.count_pop(stack.pop()))
+    }
+    // Pop and also return the length the stack was left with, both read under the same lock. A separate len()
+    // afterwards would take the lock again, and other threads could have pushed or popped in between.
+    pub fn pop_with_len(&self) -> Result<Option<(T, usize)>, StackError> {
+        let mut stack = self.inner.lock()?;
+        Ok(self.count_pop(stack.pop_with_len()))
+    }
+    // Record whether a pop found anything. The counters are only statistics, so Relaxed ordering is enough.
+    fn count_pop<U>(&self, popped: Option<U>) -> Option<U> {
+        let counter = if popped.is_some() { &self.pop_hits } else { &self.pop_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        popped
+    }
+    // Record a batch pop: one hit per element it returned, or a single miss if it came back empty.
+    fn count_batch(&self, popped: Vec<T>) -> Vec<T> {
+        if popped.is_empty() {
+            self.pop_misses.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.pop_hits.fetch_add(popped.len() as u64, Ordering::Relaxed);
+        }
+        popped
+    }
+    // The number of pops that returned an element and the number that found the stack empty, as (hits, misses),
+    // counted over every pop method since the stack was created. pop_n() and pop_all() count one hit per element
+    // they return, or one miss if they return none; a pop_if() whose predicate turned the top down is a miss too.
+    // Lots of misses mean consumers are outpacing producers and spend their time locking an empty stack.
+    pub fn stats(&self) -> (u64, u64) {
+        (self.pop_hits.load(Ordering::Relaxed), self.pop_misses.load(Ordering::Relaxed))
+    }
+    // Check the top element and pop it under the same lock, so no other thread can change the top
+    // between the check and the pop (which a separate peek followed by pop would allow).
+    pub fn pop_if<F: FnOnce(&T) -> bool>(&self, pred: F) -> Result<Option<T>, StackError> {
+        let mut stack = self.inner.lock()?;
+        Ok(self.count_pop(stack.pop_if(pred)))
+    }
+    // Pop a batch of up to n elements (top first) under a single lock acquisition.
+    pub fn pop_n(&self, n: usize) -> Result<Vec<T>, StackError> {
+        let mut stack = self.inner.lock()?;
+        Ok(self.count_batch(stack.pop_n(n)))
+    }
+    // Empty the stack and return everything that was on it (top first). Only stealing the chain of nodes
+    // happens under the lock, which is a couple of pointer moves however long the chain is; unlinking the
+    // nodes into the Vec happens after the lock is released, so producers can push again straight away.
+    pub fn pop_all(&self) -> Result<Vec<T>, StackError> {
+        let mut stack = self.inner.lock()?;
+        let mut stolen = Stack::new();
+        stolen.top = stack.top.take();
+        stolen.len = mem::take(&mut stack.len);
+        drop(stack);
+        Ok(self.count_batch(stolen.into_vec()))
+    }
+    // Copy every element (top first) while holding the lock once, giving a consistent view of the stack.
+    // Only the cloning happens under the lock, so producers are held up for as short a time as possible.
+    pub fn snapshot(&self) -> Result<Vec<T>, StackError>
+    where
+        T: Clone,
+    {
+        let stack = self.inner.lock()?;
+        Ok(stack.to_vec())
+    }
+    // Non-blocking pop: returns None straight away if another thread holds the lock (or it is poisoned),
+    // otherwise Some with the result of the pop.
+    pub fn try_pop(&self) -> Option<Option<T>> {
+        let mut stack = self.inner.try_lock().ok()?;
+        Some(self.count_pop(stack.pop()))
+    }
+    // Non-blocking push: hands the value back as Err if the lock can't be taken right now.
+    pub fn try_push(&self, data: T) -> Result<(), T> {
+        match self.inner.try_lock() {
+            Ok(mut stack) => {
+                stack.push(data);
+                Ok(())
+            }
+            Err(_) => Err(data),
+        }
+    }
+    // Cheaply sample whether another thread is holding the lock right now. The answer may be stale
+    // by the time it is returned, so it is only good for monitoring, not for deciding whether to lock.
+    pub fn is_contended(&self) -> bool {
+        matches!(self.inner.try_lock(), Err(TryLockError::WouldBlock))
+    }
+    // Lock the stack directly, for callers that need several operations to happen without
+    // other threads getting in between. Other threads block until the guard is dropped.
+    pub fn lock(&self) -> Result<MutexGuard<'_, Stack<T>>, PoisonedStack<'_, T>> {
+        self.inner.lock()
+    }
+    // Mark the lock as healthy again once the caller has decided the stack is still consistent,
+    // so later push/pop calls stop returning errors.
+    pub fn clear_poison(&self) {
+        // loom's Mutex never poisons, so there is nothing to clear under loom.
+        #[cfg(not(loom))]
+        self.inner.clear_poison();
+    }
+    // Take the Stack back out of the wrapper, recovering it from a poisoned lock (see BlockingStack::lock() for why
+    // that is safe).
+    pub fn into_inner(self) -> Stack<T> {
+        self.inner.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+    // Hand the elements over to a channel-based pipeline: a background thread pops them (top first) and sends
+    // each one down the returned Receiver. Once the stack is empty the thread ends and drops the Sender, so the
+    // receiver's iterator finishes cleanly. If the Receiver is dropped early, the thread stops at the next send
+    // and the elements it hadn't sent yet are dropped with the stack.
+    pub fn into_receiver(self) -> mpsc::Receiver<T>
+    where
+        T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for data in self.into_inner() {
+                if sender.send(data).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+}
+
+impl<T> Default for ConcurrentStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,568 @@
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Barrier};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{BlockingStack, ConcurrentStack, StackError};
+
+// How test_stack writes each stack operation to the log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    // Human-readable lines such as "Pushing 5" and "Popped 5".
+    Text,
+    // One JSON object per line (JSONL) such as {"op":"push","value":5,"thread":3}.
+    // An empty pop is logged with "value":null. Values are written with their Display output,
+    // so this format is meant for numeric stacks like the demo's Stack<i32>.
+    Json,
+}
+
+// Counts of what one run of test_stack did, returned so the demo can check its invariants once every thread is done.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreadStats {
+    // Values pushed onto the stack.
+    pub pushes: u64,
+    // Pops that got a value.
+    pub pops: u64,
+    // Pops that found the stack empty.
+    pub empty_pops: u64,
+    // Sum of the checksums of every value pushed.
+    pub pushed_sum: i64,
+    // Sum of the checksums of every value popped.
+    pub popped_sum: i64,
+}
+
+impl ThreadStats {
+    fn record_push(&mut self, checksum: i64) {
+        self.pushes += 1;
+        self.pushed_sum += checksum;
+    }
+    // Record the outcome of a pop: the popped value's checksum, or None if the stack was empty.
+    fn record_pop(&mut self, popped: Option<i64>) {
+        match popped {
+            Some(checksum) => {
+                self.pops += 1;
+                self.popped_sum += checksum;
+            }
+            None => self.empty_pops += 1,
+        }
+    }
+    // Add another thread's counts to these, for aggregating the totals of a run.
+    pub fn merge(&mut self, other: ThreadStats) {
+        self.pushes += other.pushes;
+        self.pops += other.pops;
+        self.empty_pops += other.empty_pops;
+        self.pushed_sum += other.pushed_sum;
+        self.popped_sum += other.popped_sum;
+    }
+}
+
+// A number derived from a value, summed over everything pushed and everything popped. The push/pop counts alone
+// can't catch a value that was lost while another was duplicated; the sums can: once every thread is done,
+// pushed_sum - popped_sum has to equal the checksums of the values still on the stack added up.
+pub trait Checksum {
+    fn checksum(&self) -> i64;
+}
+
+impl Checksum for i32 {
+    fn checksum(&self) -> i64 {
+        i64::from(*self)
+    }
+}
+
+impl Checksum for String {
+    // The bytes added up: cheap, and different for the demo's different numbers.
+    fn checksum(&self) -> i64 {
+        self.bytes().map(i64::from).sum()
+    }
+}
+
+impl Checksum for Task {
+    fn checksum(&self) -> i64 {
+        i64::from(self.id)
+    }
+}
+
+// Add up the checksums of everything left on the stack, to compare with pushed_sum - popped_sum.
+pub fn remaining_sum<T: Checksum>(stack: &ConcurrentStack<T>) -> Result<i64, StackError> {
+    Ok(stack.lock()?.iter().map(Checksum::checksum).sum())
+}
+
+// A small custom payload, used to run the demo with something other than a number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    pub id: i32,
+    pub label: String,
+}
+
+impl Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "task #{} ({})", self.id, self.label)
+    }
+}
+
+// The most iterations a run can do: iteration i pushes the values i * 3 + 1 to i * 3 + 3, so the last one
+// pushes iterations * 3, which has to fit in an i32.
+pub const MAX_ITERATIONS: i32 = i32::MAX / 3;
+
+// Define the test_stack function that operates on a shared ConcurrentStack of i32 and any writer
+// (a BufWriter for a File in the demo, but stdout or an in-memory Vec<u8> work just as well).
+// The stack is locked separately for every push and pop rather than once for the whole run,
+// so threads calling test_stack at the same time genuinely interleave their operations.
+// Returns how many pushes, pops and empty pops this call made, or the first error it ran into (the writer failing,
+// e.g. on a full disk, or the stack's lock being poisoned), in which case the run stops there instead of panicking.
+pub fn test_stack<W: Write>(stack: &ConcurrentStack<i32>, writer: &mut W, iterations: i32, format: LogFormat) -> Result<ThreadStats, StackError> {
+    test_stack_with(stack, writer, iterations, format, |n| n)
+}
+
+// The same sequence of operations as test_stack, for a stack of any displayable type:
+// 'make_value' turns each generated number into the value that is pushed.
+pub fn test_stack_with<T: Display + Checksum, W: Write, F: FnMut(i32) -> T>(
+    stack: &ConcurrentStack<T>,
+    writer: &mut W,
+    iterations: i32,
+    format: LogFormat,
+    make_value: F,
+) -> Result<ThreadStats, StackError> {
+    test_stack_with_options(stack, writer, iterations, format, RunOptions::default(), make_value)
+}
+
+// test_stack_with without the "Stack was empty" lines, which under heavy contention can make up much of the log.
+// Empty pops are still counted, and a single summary line with the count is written once the run is done.
+pub fn test_stack_quiet<T: Display + Checksum, W: Write, F: FnMut(i32) -> T>(
+    stack: &ConcurrentStack<T>,
+    writer: &mut W,
+    iterations: i32,
+    format: LogFormat,
+    make_value: F,
+) -> Result<ThreadStats, StackError> {
+    let options = RunOptions { quiet_empty: true, ..RunOptions::default() };
+    test_stack_with_options(stack, writer, iterations, format, options, make_value)
+}
+
+// Optional extras for a run of test_stack_with_options(). The default logs every operation and nothing else.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    // Leave out the empty-pop lines and write their count at the end instead, as test_stack_quiet does.
+    pub quiet_empty: bool,
+    // Time every push and pop and write a summary of each one's latency percentiles at the end.
+    pub timing: bool,
+}
+
+// test_stack_with, with the extras chosen in 'options'. The summary lines they add come after all the operations.
+pub fn test_stack_with_options<T: Display + Checksum, W: Write, F: FnMut(i32) -> T>(
+    stack: &ConcurrentStack<T>,
+    writer: &mut W,
+    iterations: i32,
+    format: LogFormat,
+    options: RunOptions,
+    make_value: F,
+) -> Result<ThreadStats, StackError> {
+    // Every public way into run_operations() comes through here, so this is the one place the limit is checked,
+    // before anything is pushed or logged.
+    if iterations > MAX_ITERATIONS {
+        let message = format!("at most {} iterations fit the generated values in an i32, got {}", MAX_ITERATIONS, iterations);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+    }
+    let mut latencies = options.timing.then(Latencies::default);
+    let stats = run_operations(stack, writer, iterations, format, !options.quiet_empty, latencies.as_mut(), make_value)?;
+    if options.quiet_empty {
+        match format {
+            LogFormat::Text => writeln!(writer, "Empty pops not logged: {}", stats.empty_pops)?,
+            LogFormat::Json => writeln!(writer, r#"{{"op":"empty_pops","count":{},"thread":{}}}"#, stats.empty_pops, thread_number())?,
+        }
+    }
+    if let Some(mut latencies) = latencies {
+        write_latency_summary(writer, format, "Push", &mut latencies.push)?;
+        write_latency_summary(writer, format, "Pop", &mut latencies.pop)?;
+    }
+    Ok(stats)
+}
+
+// How long each push() and pop() call on the stack took in one run. The log writes aren't included, so under
+// contention this is mostly time spent waiting for the stack's lock.
+#[derive(Default)]
+struct Latencies {
+    push: Vec<Duration>,
+    pop: Vec<Duration>,
+}
+
+// Write one line with the 50th, 90th and 99th percentile and the maximum of an operation's latencies,
+// e.g. "Push latency: p50 120.0ns, p90 250.0ns, p99 3.1µs, max 40.2µs over 1500 operations".
+fn write_latency_summary<W: Write>(writer: &mut W, format: LogFormat, op: &str, samples: &mut [Duration]) -> io::Result<()> {
+    if samples.is_empty() {
+        return Ok(());
+    }
+    samples.sort_unstable();
+    // Nearest-rank percentile: the smallest sample that at least p percent of the samples are no greater than.
+    let percentile = |p: usize| samples[(samples.len() * p).div_ceil(100) - 1];
+    let (p50, p90, p99, max) = (percentile(50), percentile(90), percentile(99), samples[samples.len() - 1]);
+    match format {
+        LogFormat::Text => {
+            writeln!(writer, "{} latency: p50 {:.1?}, p90 {:.1?}, p99 {:.1?}, max {:.1?} over {} operations", op, p50, p90, p99, max, samples.len())
+        }
+        LogFormat::Json => writeln!(
+            writer,
+            r#"{{"op":"latency","kind":"{}","p50_ns":{},"p90_ns":{},"p99_ns":{},"max_ns":{},"count":{},"thread":{}}}"#,
+            op.to_ascii_lowercase(),
+            p50.as_nanos(),
+            p90.as_nanos(),
+            p99.as_nanos(),
+            max.as_nanos(),
+            samples.len(),
+            thread_number()
+        ),
+    }
+}
+
+// The operations behind test_stack_with_options; 'log_empty' says whether empty pops get a log line, and if
+// 'latencies' is given every push and pop's duration is added to it.
+fn run_operations<T: Display + Checksum, W: Write, F: FnMut(i32) -> T>(
+    stack: &ConcurrentStack<T>,
+    writer: &mut W,
+    iterations: i32,
+    format: LogFormat,
+    log_empty: bool,
+    latencies: Option<&mut Latencies>,
+    mut make_value: F,
+) -> Result<ThreadStats, StackError> {
+    let mut tally = Tally { stats: ThreadStats::default(), latencies };
+    // Iterate 'iterations' times (500 by default), using `i` as the loop counter.
+    for i in 0..iterations {
+        // 3 intermixed push and pop operations
+        // 'i * 3 + _' is a way to generate distinct values for each iteration of the loop that are evenly spaced apart
+        let next_value1 = i * 3 + 1;
+        tally.push(push_and_log(stack, writer, make_value(next_value1), format)?);
+
+        let next_value2 = i * 3 + 2;
+        tally.push(push_and_log(stack, writer, make_value(next_value2), format)?);
+
+        tally.pop(pop_and_maybe_log(stack, writer, format, log_empty, T::checksum)?);
+
+        let next_value3 = i * 3 + 3;
+        tally.push(push_and_log(stack, writer, make_value(next_value3), format)?);
+
+        tally.pop(pop_and_maybe_log(stack, writer, format, log_empty, T::checksum)?);
+
+        tally.pop(pop_and_maybe_log(stack, writer, format, log_empty, T::checksum)?);
+    }
+    Ok(tally.stats)
+}
+
+// What run_operations records about each operation: the counts and sums, plus the durations if they are timed.
+struct Tally<'a> {
+    stats: ThreadStats,
+    latencies: Option<&'a mut Latencies>,
+}
+
+impl Tally<'_> {
+    fn push(&mut self, (checksum, took): (i64, Duration)) {
+        self.stats.record_push(checksum);
+        if let Some(latencies) = self.latencies.as_mut() {
+            latencies.push.push(took);
+        }
+    }
+    fn pop(&mut self, (popped, took): (Option<i64>, Duration)) {
+        self.stats.record_pop(popped);
+        if let Some(latencies) = self.latencies.as_mut() {
+            latencies.pop.push(took);
+        }
+    }
+}
+
+// Run test_stack 'runs' times in a row on the current thread, exactly what 'runs' demo threads do but without
+// any interleaving, so the same arguments always produce the same log. This separates "is the stack logic
+// correct" from "does concurrency break it": the log can be compared against a saved golden copy.
+pub fn replay<T: Display + Checksum, W: Write, F: FnMut(i32) -> T>(
+    stack: &ConcurrentStack<T>,
+    writer: &mut W,
+    runs: usize,
+    iterations: i32,
+    format: LogFormat,
+    mut make_value: F,
+) -> Result<ThreadStats, StackError> {
+    let mut totals = ThreadStats::default();
+    for _ in 0..runs {
+        totals.merge(test_stack_with(stack, writer, iterations, format, &mut make_value)?);
+    }
+    Ok(totals)
+}
+
+// Spawn 'threads' threads that each run f(index, start), and hand back their join handles in index order.
+// 'start' is one Barrier shared by all of them: a thread calling start.wait() is held there until every thread has
+// called it, so whatever comes after begins at the same moment everywhere. Spawning in a loop otherwise gives the
+// first threads a head start, and with short runs they can finish before the last ones have even started, so the
+// threads never actually contend. Every thread has to reach start.wait(), even one that is about to fail,
+// or the others wait forever.
+pub fn spawn_together<R, F>(threads: usize, f: F) -> Vec<JoinHandle<R>>
+where
+    R: Send + 'static,
+    F: Fn(usize, &Barrier) -> R + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    let start = Arc::new(Barrier::new(threads));
+    (0..threads)
+        .map(|index| {
+            let f = Arc::clone(&f);
+            let start = Arc::clone(&start);
+            thread::spawn(move || f(index, &start))
+        })
+        .collect()
+}
+
+// What a work-queue worker made of one Task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TaskResult {
+    pub task_id: i32,
+    // The task's "work": its id squared.
+    pub value: i64,
+}
+
+// One worker of the work-queue demo: pop tasks off the shared 'tasks' stack until it is empty, and push a result
+// for each onto the shared 'results' stack. Every task is queued before the workers start, so an empty pop means
+// the work is done. Returns how many tasks this worker handled.
+pub fn work_queue_worker(tasks: &ConcurrentStack<Task>, results: &ConcurrentStack<TaskResult>) -> Result<u64, StackError> {
+    let mut handled = 0;
+    while let Some(task) = tasks.pop()? {
+        results.push(TaskResult { task_id: task.id, value: i64::from(task.id) * i64::from(task.id) })?;
+        handled += 1;
+    }
+    Ok(handled)
+}
+
+// The work-queue demo: queue 'task_count' tasks on one stack, let 'workers' threads take them off it and push
+// their results onto a second stack. Data flows between the threads rather than each running its own loop,
+// and whichever worker is free takes the next task. Returns the number of tasks each worker handled
+// and the stack of results.
+pub fn run_work_queue(task_count: i32, workers: usize) -> Result<(Vec<u64>, ConcurrentStack<TaskResult>), StackError> {
+    let tasks = ConcurrentStack::new();
+    tasks.push_iter((1..=task_count).map(|id| Task { id, label: format!("square {}", id) }))?;
+    let results = ConcurrentStack::new();
+    // Scoped threads can borrow both stacks, so no Arc is needed.
+    let handled = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|_| scope.spawn(|| work_queue_worker(&tasks, &results))).collect();
+        handles.into_iter().map(|handle| handle.join().expect("worker panicked")).collect::<Result<Vec<u64>, StackError>>()
+    })?;
+    Ok((handled, results))
+}
+
+// What a run of the producer/consumer demo did: how many items each consumer popped, the sum of every popped
+// item (which should be the sum of 1..=items, the values the producer pushed), and how long the whole run took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProducerConsumerReport {
+    pub consumed: Vec<u64>,
+    pub sum: u64,
+    pub elapsed: Duration,
+}
+
+// The producer/consumer demo: one producer pushes the values 1..=items onto a BlockingStack that holds at most
+// 'capacity' of them, while 'consumers' threads pop them off with pop_wait(). The producer is never slowed down
+// on purpose, so it soon finds the stack full and has to wait in push() until a consumer makes room. Once it has
+// pushed everything it closes the stack, which lets the consumers drain what is left and then finish.
+pub fn producer_consumer(items: u64, consumers: usize, capacity: usize) -> ProducerConsumerReport {
+    let stack = BlockingStack::bounded(capacity);
+    let start = Instant::now();
+    let consumed: Vec<(u64, u64)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..consumers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let (mut count, mut sum) = (0, 0);
+                    while let Some(value) = stack.pop_wait() {
+                        count += 1;
+                        sum += value;
+                    }
+                    (count, sum)
+                })
+            })
+            .collect();
+        for value in 1..=items {
+            stack.push(value);
+        }
+        stack.close();
+        handles.into_iter().map(|handle| handle.join().expect("consumer panicked")).collect()
+    });
+    ProducerConsumerReport {
+        consumed: consumed.iter().map(|&(count, _)| count).collect(),
+        sum: consumed.iter().map(|&(_, sum)| sum).sum(),
+        elapsed: start.elapsed(),
+    }
+}
+
+// Write a log line for the value about to be pushed, then push it and return its checksum along with how long
+// the push itself took. If the log line can't be written, the error is returned and the value is not pushed.
+fn push_and_log<T: Display + Checksum, W: Write>(stack: &ConcurrentStack<T>, writer: &mut W, value: T, format: LogFormat) -> Result<(i64, Duration), StackError> {
+    let checksum = value.checksum();
+    match format {
+        LogFormat::Text => writeln!(writer, "Pushing {}", value)?,
+        LogFormat::Json => writeln!(writer, r#"{{"op":"push","value":{},"thread":{}}}"#, value, thread_number())?,
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(thread = %thread_number(), value = %value, "push");
+    // Lock the stack just for this push, returning StackError::Poisoned if another thread poisoned the lock.
+    let start = Instant::now();
+    stack.push(value)?;
+    Ok((checksum, start.elapsed()))
+}
+
+// Define a generic function pop_and_log that accepts a stack and a writer.
+// The generic type T must implement the 'Display' trait for formatting, and W can be anything that implements 'Write'.
+// Returns whether a value was popped, or the error the writer or the stack's lock reported.
+pub fn pop_and_log<T: Display, W: Write>(stack: &ConcurrentStack<T>, writer: &mut W, format: LogFormat) -> Result<bool, StackError> {
+    Ok(pop_and_maybe_log(stack, writer, format, true, |_| 0)?.0.is_some())
+}
+
+// pop_and_log, with the log line for an empty stack only written if 'log_empty' is set.
+// Returns the popped value's checksum (None if the stack was empty) and how long the pop itself took.
+fn pop_and_maybe_log<T: Display, W: Write, C: Fn(&T) -> i64>(
+    stack: &ConcurrentStack<T>,
+    writer: &mut W,
+    format: LogFormat,
+    log_empty: bool,
+    checksum: C,
+) -> Result<(Option<i64>, Duration), StackError> {
+    // Attempt to pop a value from the stack, locking it just for this pop.
+    let start = Instant::now();
+    let popped = stack.pop()?;
+    let took = start.elapsed();
+    // The fields are only formatted if a subscriber is listening for debug events.
+    #[cfg(feature = "tracing")]
+    match &popped {
+        Some(value) => tracing::debug!(thread = %thread_number(), value = %value, "pop"),
+        None => tracing::debug!(thread = %thread_number(), "pop from an empty stack"),
+    }
+    let popped_sum = popped.as_ref().map(checksum);
+    let result = match (popped, format) {
+        // If a value is successfully popped (i.e., the stack was not empty), write a log message stating the popped value.
+        (Some(value), LogFormat::Text) => writeln!(writer, "Popped {}", value),
+        (Some(value), LogFormat::Json) => writeln!(writer, r#"{{"op":"pop","value":{},"thread":{}}}"#, value, thread_number()),
+        // In quiet mode an empty pop is only counted. The pop's own result decides this: checking peek() or len()
+        // first would race with the other threads.
+        (None, _) if !log_empty => Ok(()),
+        // If no value could be popped (i.e., the stack was empty), write a log message stating that the stack was empty.
+        (None, LogFormat::Text) => writeln!(writer, "Stack was empty, nothing to pop"),
+        (None, LogFormat::Json) => writeln!(writer, r#"{{"op":"pop","value":null,"thread":{}}}"#, thread_number()),
+    };
+    result?;
+    Ok((popped_sum, took))
+}
+
+// One thread's handle on a log shared by every thread. Each complete line written through it is prefixed with
+// "[T{n}] " (n being the thread's index in the spawn loop) so the lines can be grouped by thread.
+// Every thread gets its own W, e.g. its own File::try_clone() of a log opened in append mode, so threads
+// don't take turns on one locked writer. What keeps their lines apart is that each tagged line goes to W in
+// a single write_all(): the OS appends a write to an O_APPEND file in one piece, but would interleave
+// bytes freely if a line were split over several writes. Nothing is buffered beyond the current line.
+pub struct TaggedWriter<W: Write> {
+    inner: W,
+    thread: usize,
+    // Bytes of the current line, held back until its newline arrives.
+    line: Vec<u8>,
+}
+
+impl<W: Write> TaggedWriter<W> {
+    pub fn new(inner: W, thread: usize) -> Self {
+        Self { inner, thread, line: Vec::new() }
+    }
+}
+
+impl<W: Write> Write for TaggedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        while let Some(end) = self.line.iter().position(|&byte| byte == b'\n') {
+            let rest = self.line.split_off(end + 1);
+            let line = mem::replace(&mut self.line, rest);
+            let mut tagged = format!("[T{}] ", self.thread).into_bytes();
+            tagged.extend_from_slice(&line);
+            self.inner.write_all(&tagged)?;
+        }
+        Ok(buf.len())
+    }
+    // A line still missing its newline stays held back rather than written in pieces.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// A writer that flushes the writer it wraps after every 'every' complete lines, so a BufWriter's recent
+// log lines reach the file while the run is still going and mostly survive a crash, instead of all
+// sitting in memory until the final flush.
+pub struct PeriodicFlush<W: Write> {
+    inner: W,
+    every: usize,
+    // Newlines written since the last flush.
+    lines: usize,
+}
+
+impl<W: Write> PeriodicFlush<W> {
+    pub fn new(inner: W, every: usize) -> Self {
+        assert!(every > 0, "PeriodicFlush needs to flush every 1 or more lines");
+        Self { inner, every, lines: 0 }
+    }
+}
+
+impl<W: Write> Write for PeriodicFlush<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.lines += buf[..written].iter().filter(|&&byte| byte == b'\n').count();
+        if self.lines >= self.every {
+            self.flush()?;
+        }
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.lines = 0;
+        self.inner.flush()
+    }
+}
+
+// A writer that flushes the writer it wraps when it is dropped, including while a panic unwinds the thread,
+// so the lines logged before the panic still reach the file. Errors from that last flush have nowhere to go
+// and are ignored, so code that finishes normally should still call flush() itself and check the result.
+// (std's BufWriter does flush when dropped, but wrappers around it, and writers in general, aren't required to.)
+pub struct FlushOnDrop<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> FlushOnDrop<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> Write for FlushOnDrop<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for FlushOnDrop<W> {
+    fn drop(&mut self) {
+        let _ = self.inner.flush();
+    }
+}
+
+// The numeric part of the current thread's id, used to tell apart the JSON lines of concurrent threads.
+// ThreadId has no stable accessor for the number, so it is taken from the Debug output "ThreadId(N)".
+pub fn thread_number() -> String {
+    let id = format!("{:?}", thread::current().id());
+    id.trim_start_matches("ThreadId(").trim_end_matches(')').to_string()
+}
+
+// Concatenate the files in 'parts', in order, into 'merged', then delete the parts.
+pub fn merge_files<P: AsRef<Path>>(parts: &[P], merged: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(merged)?);
+    for part in parts {
+        io::copy(&mut File::open(part)?, &mut writer)?;
+    }
+    writer.flush()?;
+    for part in parts {
+        fs::remove_file(part)?;
+    }
+    Ok(())
+}
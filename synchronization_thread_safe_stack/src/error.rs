@@ -0,0 +1,51 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::PoisonError;
+
+// Everything that can go wrong in the crate's fallible APIs, so a caller can handle (or log and propagate with ?)
+// a failure instead of the library panicking.
+#[derive(Debug)]
+pub enum StackError {
+    // Another thread panicked while holding the stack's lock. The stack itself is still consistent;
+    // ConcurrentStack::clear_poison() makes it usable again.
+    Poisoned,
+    // Writing a log line (or another I/O operation) failed.
+    Io(io::Error),
+    // A bounded stack was already holding as many elements as it may.
+    CapacityExceeded,
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackError::Poisoned => write!(f, "another thread panicked while holding the stack's lock"),
+            // Show the I/O error as it is; wrapping it only says where it came from, not what went wrong.
+            StackError::Io(err) => write!(f, "{}", err),
+            StackError::CapacityExceeded => write!(f, "the stack is full"),
+        }
+    }
+}
+
+impl Error for StackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StackError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StackError {
+    fn from(err: io::Error) -> Self {
+        StackError::Io(err)
+    }
+}
+
+// Lets ConcurrentStack's methods use ? on a lock() result. The guard inside the PoisonError is dropped;
+// a caller who wants to inspect the stack anyway can still go through ConcurrentStack::lock().
+impl<G> From<PoisonError<G>> for StackError {
+    fn from(_: PoisonError<G>) -> Self {
+        StackError::Poisoned
+    }
+}
@@ -0,0 +1,762 @@
+// The core data structures only need an allocator, so without the default "std" feature the crate is no_std
+// and builds for allocator-only (e.g. embedded) targets. Everything involving threads, locks or files
+// (ConcurrentStack, BlockingStack, LockFreeStack, ShardedStack, StackError and the demo) needs "std".
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod arena;
+#[cfg(feature = "std")]
+mod blocking;
+mod bounded;
+#[cfg(feature = "std")]
+mod concurrent;
+#[cfg(feature = "std")]
+pub mod demo;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod lock_free;
+mod min_max;
+mod monotonic;
+mod priority;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "std")]
+mod sharded;
+
+pub use arena::{ArenaIter, ArenaStack};
+#[cfg(feature = "std")]
+pub use blocking::BlockingStack;
+pub use bounded::BoundedStack;
+#[cfg(feature = "std")]
+pub use concurrent::{ConcurrentStack, PoisonedStack};
+#[cfg(feature = "std")]
+pub use error::StackError;
+#[cfg(feature = "std")]
+pub use lock_free::LockFreeStack;
+pub use min_max::MinMaxStack;
+pub use monotonic::MonotonicStack;
+pub use priority::PriorityStack;
+#[cfg(feature = "std")]
+pub use sharded::ShardedStack;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem::{self, MaybeUninit};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+// The length of a stack after 'removed' of its nodes were unlinked. Every method that unlinks nodes goes
+// through this, so a len that has fallen out of step with the chain panics with the same message everywhere
+// instead of wrapping around in release builds. (It takes the len rather than &mut self so it can be called
+// while a cursor still borrows the chain.)
+fn shrunk_len(len: usize, removed: usize) -> usize {
+    len.checked_sub(removed).expect("Stack length out of sync with its nodes")
+}
+
+// A Box is needed (allocates memory on the heap) to avoid recursive types.
+pub struct StackNode<T> {
+    data: T,
+    next: Option<Box<StackNode<T>>>,
+}
+
+// A singly linked LIFO stack. Every walk over the chain of nodes (drop, clear, clone, comparison, hashing,
+// formatting, collecting, reversing, ...) is a loop, never recursion, so a stack millions of elements deep
+// can be built and torn down without overflowing the thread's call stack. New methods must keep it that way.
+// A Stack does no locking itself. Like Vec<T>, it is Send when T is Send and Sync when T is Sync, so a Stack<i32>
+// can be moved to another thread but a Stack<Rc<i32>> can't (tests/ui checks that). To push and pop from several
+// threads at once, put it behind Arc<Mutex<_>> or use ConcurrentStack.
+pub struct Stack<T> {
+    top: Option<Box<StackNode<T>>>,
+    // Number of nodes in the chain, kept up to date by push and pop so len() is O(1).
+    len: usize,
+    // Allocations of popped nodes, kept to be reused by the next push instead of freeing and reallocating.
+    // Their contents have already been moved out, hence MaybeUninit.
+    free: Vec<Box<MaybeUninit<StackNode<T>>>>,
+    // The most allocations 'free' may hold; 0 (the default) turns pooling off.
+    max_free: usize,
+    // Called after every push and pop when set; see on_event(). The closure has to be Send + Sync so
+    // that a Stack<T> stays exactly as Send and Sync as T is.
+    on_event: Option<Box<EventHook<T>>>,
+}
+
+type EventHook<T> = dyn FnMut(StackEvent<&T>) + Send + Sync;
+
+// What an on_event() callback is told about: the element that was just pushed (now the top)
+// or the element that was just popped (about to be handed back to the caller).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackEvent<T> {
+    Push(T),
+    Pop(T),
+}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Self::with_pool(0)
+    }
+    // Create a stack that keeps up to max_free popped node allocations around for reuse,
+    // so workloads that push and pop heavily stop paying for an allocation and a free per operation.
+    pub fn with_pool(max_free: usize) -> Self {
+        Self { top: None, len: 0, free: Vec::new(), max_free, on_event: None }
+    }
+    // Make sure the next 'additional' pushes don't allocate, as far as the pool allows: on a stack created with
+    // with_pool(), node allocations are created up front and put in the pool until it holds 'additional' of them
+    // (or is full). Without a pool there is nowhere to keep them, so this does nothing. ArenaStack::reserve()
+    // is the same call for the arena, where it grows the Vec the nodes live in.
+    pub fn reserve(&mut self, additional: usize) {
+        let target = additional.min(self.max_free);
+        if self.free.len() >= target {
+            return;
+        }
+        self.free.reserve(target - self.free.len());
+        while self.free.len() < target {
+            self.free.push(Box::new(MaybeUninit::uninit()));
+        }
+    }
+    // Build a stack of n elements by pushing f(0), f(1), ..., f(n - 1) in that order, like array::from_fn.
+    // The last one pushed, f(n - 1), ends up on top.
+    pub fn from_fn<F: FnMut(usize) -> T>(n: usize, f: F) -> Self {
+        (0..n).map(f).collect()
+    }
+    // Build a stack holding clones of the slice's elements, pushed in order, so the slice's last element is on top.
+    pub fn with_initial(items: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        items.iter().cloned().collect()
+    }
+    pub fn push(&mut self, data: T) {
+        // Work out the new length first, so an overflow panics before the stack is touched.
+        let len = self.len.checked_add(1).expect("Stack length overflowed usize");
+        // Create a new pointer to a StackNode and set its 'next' pointer to the
+        // take() takes the value out of the option, leaving a None in its place.
+        // This effectively removes the current top node in the stack,
+        // relocating it to the 'next' pointer in the new_node.
+        let node = StackNode {
+            data,
+            next: self.top.take()
+        };
+        // Reuse a pooled allocation if there is one, otherwise allocate a new Box.
+        let new_node = match self.free.pop() {
+            Some(slot) => Box::write(slot, node),
+            None => Box::new(node),
+        };
+        // Since we used take() and left a None value at the top of the stack, we need to update it
+        // Set the new top pointer (currently None since we took it) to the new_node
+        self.top = Some(new_node);
+        self.len = len;
+        if let (Some(hook), Some(top)) = (self.on_event.as_mut(), self.top.as_ref()) {
+            hook(StackEvent::Push(&top.data));
+        }
+    }
+    // Register a callback that is told about every push and pop from now on (replacing any earlier one),
+    // to collect metrics or trace operations without touching the call sites. Every method that adds or
+    // removes elements one at a time through push() or pop() (push_iter, pop_n, drain, ...) fires it too;
+    // methods that relink whole chains of nodes at once (clear, append, split_off, ...) don't.
+    pub fn on_event<F: FnMut(StackEvent<&T>) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.on_event = Some(Box::new(hook));
+    }
+    // Remove the callback registered with on_event(), if any.
+    pub fn clear_on_event(&mut self) {
+        self.on_event = None;
+    }
+    // Push unless the length would overflow usize, handing the value back instead of panicking.
+    // That takes a usize::MAX-element stack, so this only matters where len could have drifted out of sync.
+    pub fn checked_push(&mut self, data: T) -> Result<(), T> {
+        if self.len == usize::MAX {
+            return Err(data);
+        }
+        self.push(data);
+        Ok(())
+    }
+    // An empty stack that claims to hold 'len' elements, for exercising the length bookkeeping near its limits
+    // without allocating that many nodes. Only built with the test-util feature, which tests/ turns on, since
+    // the length it claims doesn't match the (empty) chain and other methods rely on the two agreeing.
+    #[cfg(feature = "test-util")]
+    #[doc(hidden)]
+    pub fn with_forced_len(len: usize) -> Self {
+        Self { top: None, len, free: Vec::new(), max_free: 0, on_event: None }
+    }
+    // Push every element of the iterator in order, so the last one ends up on top.
+    pub fn push_iter<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push(data);
+        }
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        // Unlike .take(), .map() DOES unwrap the Option, so we are just dealing with a Box<StackNode<T>>.
+        // Box acts like a reference, so we can access the fields of the StackNode inside with '.'
+        self.top.take().map(|node| {
+            let (ret, next) = self.recycle(node);
+            // Set the new top of the stack to the next node it pointed to
+            self.top = next;
+            // There was a node to pop, so len can only be 0 here if the bookkeeping is broken.
+            self.len = shrunk_len(self.len, 1);
+            if let Some(hook) = self.on_event.as_mut() {
+                hook(StackEvent::Pop(&ret));
+            }
+            ret
+        })
+    }
+    // pop(), also returning the length the stack is left with, so a caller holding a lock
+    // (e.g. ConcurrentStack::pop_with_len) gets both without a separate len() call.
+    pub fn pop_with_len(&mut self) -> Option<(T, usize)> {
+        self.pop().map(|data| (data, self.len))
+    }
+    // Move the data and 'next' link out of a node that has been taken off the stack. If the pool has room,
+    // the node's allocation goes into it instead of being freed.
+    fn recycle(&mut self, node: Box<StackNode<T>>) -> (T, Option<Box<StackNode<T>>>) {
+        if self.free.len() >= self.max_free {
+            let node = *node;
+            return (node.data, node.next);
+        }
+        let raw = Box::into_raw(node);
+        // Safety: 'raw' came from Box::into_raw, so it points to a valid, initialized StackNode. Its fields are
+        // read out exactly once, after which the allocation is only ever treated as uninitialized memory:
+        // MaybeUninit has the same layout as StackNode and never drops its contents.
+        unsafe {
+            let StackNode { data, next } = raw.read();
+            self.free.push(Box::from_raw(raw.cast::<MaybeUninit<StackNode<T>>>()));
+            (data, next)
+        }
+    }
+    // Pop the top element only if the predicate returns true for it; otherwise leave the stack alone and return None.
+    pub fn pop_if<F: FnOnce(&T) -> bool>(&mut self, pred: F) -> Option<T> {
+        match self.peek() {
+            Some(data) if pred(data) => self.pop(),
+            _ => None,
+        }
+    }
+    // Swap the top two elements (like Forth's SWAP). Does nothing if there are fewer than two.
+    pub fn swap_top(&mut self) {
+        if let Some(top) = self.top.as_mut() {
+            if let Some(second) = top.next.as_mut() {
+                mem::swap(&mut top.data, &mut second.data);
+            }
+        }
+    }
+    // Pop up to n elements, returned in the order they were popped (top first).
+    // Stops early if the stack runs out, so the Vec may be shorter than n.
+    pub fn pop_n(&mut self, n: usize) -> Vec<T> {
+        let mut popped = Vec::with_capacity(n.min(self.len));
+        while popped.len() < n {
+            match self.pop() {
+                Some(data) => popped.push(data),
+                None => break,
+            }
+        }
+        popped
+    }
+    pub fn peek(&self) -> Option<&T> {
+        // .as_ref() turns &Option<Box<StackNode<T>>> into Option<&Box<StackNode<T>>>,
+        // so we can look at the top node without taking it out of the stack.
+        self.top.as_ref().map(|node| &node.data)
+    }
+    // References to up to n elements from the top down, without removing anything.
+    pub fn peek_n(&self, n: usize) -> Vec<&T> {
+        self.iter().take(n).collect()
+    }
+    // The element 'index' places down from the top (0 is the top), found by walking the chain.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        // Same as peek(), but .as_mut() hands out a mutable reference to the top node's data.
+        self.top.as_mut().map(|node| &mut node.data)
+    }
+    // Put 'data' in place of the top element and return the old one, without the unlink and relink of a pop()
+    // followed by a push(). On an empty stack nothing is pushed and 'data' is dropped, returning None.
+    // The length doesn't change, and since nothing is pushed or popped the on_event() hook isn't called.
+    pub fn replace_top(&mut self, data: T) -> Option<T> {
+        self.peek_mut().map(|top| mem::replace(top, data))
+    }
+    // Like replace_top(), but pushes 'data' if the stack is empty, so afterwards it is always on top.
+    pub fn replace_top_or_push(&mut self, data: T) -> Option<T> {
+        match self.peek_mut() {
+            Some(top) => Some(mem::replace(top, data)),
+            None => {
+                self.push(data);
+                None
+            }
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    // An estimate of the heap memory the stack holds: one node allocation per element plus the pooled
+    // allocations kept for reuse. Memory owned by the elements themselves (e.g. a String's buffer) isn't
+    // counted. Watching this over time shows whether a long-running stack keeps growing.
+    pub fn memory_bytes(&self) -> usize {
+        (self.len + self.free.len()) * mem::size_of::<StackNode<T>>()
+    }
+    pub fn is_empty(&self) -> bool {
+        // An empty stack has no top node; len is 0 exactly when that is the case.
+        self.top.is_none()
+    }
+    pub fn clear(&mut self) {
+        // Walk the chain iteratively, taking each node out of the stack one at a time.
+        while let Some(mut node) = self.top.take() {
+            // Detach the rest of the chain before 'node' goes out of scope,
+            // so each node is dropped with no further children attached.
+            self.top = node.next.take();
+        }
+        self.len = 0;
+    }
+    // Remove every element the predicate returns true for and return them (top first).
+    // The remaining nodes are re-linked around the removed ones, so they keep their relative order.
+    pub fn drain_filter<F: FnMut(&T) -> bool>(&mut self, pred: F) -> Vec<T> {
+        let mut removed = Vec::new();
+        self.unlink_where(pred, |data| removed.push(data));
+        removed
+    }
+    // Keep only the elements the predicate returns true for, like Vec::retain, preserving their order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut pred: F) {
+        self.unlink_where(|data| !pred(data), drop);
+    }
+    // Unlink every node whose data the predicate returns true for, handing each removed element to 'removed'.
+    fn unlink_where<F: FnMut(&T) -> bool, R: FnMut(T)>(&mut self, mut pred: F, mut removed: R) {
+        // 'cursor' is the link (either self.top or some node's 'next') that points at the node being examined.
+        let mut cursor = &mut self.top;
+        while let Some(node) = cursor {
+            if pred(&node.data) {
+                // Unlink the node by pointing the link at the node below it.
+                let mut node = cursor.take().unwrap();
+                *cursor = node.next.take();
+                self.len = shrunk_len(self.len, 1);
+                removed(node.data);
+            } else {
+                // Keep the node and move the cursor on to its 'next' link.
+                cursor = &mut cursor.as_mut().unwrap().next;
+            }
+        }
+    }
+    // Collapse every run of equal adjacent elements into one, like Vec::dedup but along the chain:
+    // a node is removed when its data equals the data of the node directly below it.
+    pub fn dedup_consecutive(&mut self)
+    where
+        T: PartialEq,
+    {
+        let mut cursor = &mut self.top;
+        while let Some(node) = cursor {
+            let duplicate = node.next.as_ref().is_some_and(|below| below.data == node.data);
+            if duplicate {
+                // Unlink the node; the cursor stays put, now pointing at the equal node below.
+                let mut node = cursor.take().unwrap();
+                *cursor = node.next.take();
+                self.len = shrunk_len(self.len, 1);
+            } else {
+                cursor = &mut cursor.as_mut().unwrap().next;
+            }
+        }
+    }
+    // Walk the chain and report whether any element equals 'value'.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|data| data == value)
+    }
+    // Whether every element is >= the one below it, i.e. the top is the largest and values never increase on the
+    // way down, as pushing values in ascending order leaves them. (A MonotonicStack keeps the opposite order.)
+    // Empty and single-element stacks are trivially sorted. An element that can't be compared with its
+    // neighbour (a NaN float) makes the stack unsorted.
+    pub fn is_sorted_descending(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.iter().zip(self.iter().skip(1)).all(|(above, below)| above >= below)
+    }
+    // How many elements the predicate returns true for, found by walking the chain without removing anything.
+    pub fn count_where<F: FnMut(&T) -> bool>(&self, mut pred: F) -> usize {
+        self.iter().filter(|data| pred(data)).count()
+    }
+    // Reverse the stack in place, so the old bottom becomes the new top.
+    // Only the 'next' pointers are re-linked; no node is allocated, freed or moved.
+    pub fn reverse(&mut self) {
+        let mut reversed: Option<Box<StackNode<T>>> = None;
+        let mut rest = self.top.take();
+        while let Some(mut node) = rest {
+            // Detach the node from the rest of the chain and put it on top of the reversed chain.
+            rest = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+        self.top = reversed;
+    }
+    // Rotate the top k elements so the k-th from the top becomes the new top and the k-1 above it move down
+    // one place, like Forth's ROT for k = 3. Nothing happens if k is 0, 1 or more than len().
+    // The k-th node is unlinked and relinked at the top, so only the first k nodes are walked.
+    pub fn rotate(&mut self, k: usize) {
+        if k < 2 || k > self.len {
+            return;
+        }
+        // Find the link pointing at the k-th node.
+        let mut cursor = &mut self.top;
+        for _ in 0..k - 1 {
+            cursor = &mut cursor.as_mut().unwrap().next;
+        }
+        // Close the gap it leaves, then put it on top.
+        let mut node = cursor.take().unwrap();
+        *cursor = node.next.take();
+        node.next = self.top.take();
+        self.top = Some(node);
+    }
+    // Move all of other's elements on top of this stack, keeping their order, and leave other empty.
+    // Only other's chain is walked (to find its bottom node), so this is O(other.len()).
+    pub fn append(&mut self, other: &mut Stack<T>) {
+        // Find the 'next' link of other's bottom node (or other.top itself if other is empty).
+        let mut cursor = &mut other.top;
+        while let Some(node) = cursor {
+            cursor = &mut node.next;
+        }
+        // Hang this stack's chain below other's bottom node, then make other's top our top.
+        *cursor = self.top.take();
+        self.top = other.top.take();
+        self.len = self.len.checked_add(mem::take(&mut other.len)).expect("Stack length overflowed usize");
+    }
+    // Split the stack 'at' elements down from the top: self keeps the top 'at' elements and the rest
+    // (the bottom part) is returned as a new stack. Only the first 'at' nodes are walked, to sever one link.
+    // If 'at' is len() or more, self keeps everything and the returned stack is empty.
+    pub fn split_off(&mut self, at: usize) -> Stack<T> {
+        let mut bottom = Stack::with_pool(self.max_free);
+        if at >= self.len {
+            return bottom;
+        }
+        // Find the 'next' link of the at-th node (or self.top when at is 0).
+        let mut cursor = &mut self.top;
+        for _ in 0..at {
+            cursor = &mut cursor.as_mut().unwrap().next;
+        }
+        // Cut the chain there: everything below becomes the other stack.
+        bottom.top = cursor.take();
+        bottom.len = shrunk_len(self.len, at);
+        self.len = at;
+        bottom
+    }
+    // Copy the elements into a Vec, top first, leaving the stack as it is.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.iter().cloned().collect()
+    }
+    // Drain the stack into a Vec, top first.
+    pub fn into_vec(self) -> Vec<T> {
+        self.into_iter().collect()
+    }
+    // The elements in the order they were pushed (bottom first), the way a FIFO queue would hand them out.
+    // The chain is reversed in place once and then drained, so no second Vec or reversal pass is needed.
+    pub fn into_fifo_vec(mut self) -> Vec<T> {
+        self.reverse();
+        self.into_vec()
+    }
+    // Split the stack in two: the elements the predicate returns true for, and the rest. Both keep the elements
+    // in the order they had here. The predicate is called bottom first, since that is the order the elements are
+    // pushed onto the two new stacks.
+    pub fn partition<F: FnMut(&T) -> bool>(mut self, mut pred: F) -> (Stack<T>, Stack<T>) {
+        let (mut matching, mut rest) = (Stack::new(), Stack::new());
+        // Turned upside down, popping gives the bottom element first, and pushing in that order rebuilds the
+        // original order.
+        self.reverse();
+        for data in self {
+            if pred(&data) {
+                matching.push(data);
+            } else {
+                rest.push(data);
+            }
+        }
+        (matching, rest)
+    }
+    // Drain the stack into a Vec sorted in ascending order, regardless of the order things were pushed in.
+    pub fn into_sorted_vec(self) -> Vec<T>
+    where
+        T: Ord,
+    {
+        let mut elements = self.into_vec();
+        elements.sort();
+        elements
+    }
+    // Consume the stack, popping each element (top first) and combining it into an accumulator,
+    // e.g. stack.fold(0, |sum, n| sum + n). It is a plain loop over pop(), so any depth is fine.
+    pub fn fold<B, F: FnMut(B, T) -> B>(mut self, init: B, mut f: F) -> B {
+        let mut acc = init;
+        while let Some(data) = self.pop() {
+            acc = f(acc, data);
+        }
+        acc
+    }
+    // Consume the stack and build a new one from f applied to each element, in the same top-to-bottom order.
+    // f sees the elements top first. Popping and pushing reverses the order, so the new chain is flipped back
+    // with reverse(), which only relinks nodes.
+    pub fn map<U, F: FnMut(T) -> U>(mut self, mut f: F) -> Stack<U> {
+        let mut mapped = Stack::with_pool(self.max_free);
+        while let Some(data) = self.pop() {
+            mapped.push(f(data));
+        }
+        mapped.reverse();
+        mapped
+    }
+    // Count how many times each value appears, ignoring order. Concurrent pops leave the stack in an
+    // unpredictable order, so tests can compare these counts instead of the exact sequence.
+    // HashMap lives in std, so this needs the "std" feature.
+    #[cfg(feature = "std")]
+    pub fn to_multiset(&self) -> HashMap<T, usize>
+    where
+        T: Eq + Hash + Clone,
+    {
+        let mut counts = HashMap::new();
+        for value in self.iter() {
+            *counts.entry(value.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+    // Pop elements lazily, one per call to next(). Unlike into_iter() this only borrows the stack,
+    // so anything not yet drained when the Drain is dropped stays on the stack.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { stack: self }
+    }
+    // Like drain(), but pops up to chunk_size elements (top first) per call to next(), so a huge stack can be
+    // streamed out, e.g. to a file, with only one chunk in memory at a time instead of a Vec of everything.
+    // Every chunk but the last has exactly chunk_size elements. Panics if chunk_size is 0.
+    pub fn drain_chunks(&mut self, chunk_size: usize) -> DrainChunks<'_, T> {
+        assert!(chunk_size > 0, "drain_chunks needs a chunk size of at least 1");
+        DrainChunks { stack: self, chunk_size }
+    }
+    // Push a value that is popped again when the returned guard goes out of scope, unless the guard is
+    // committed first. Handy for stack machines that push on entering a scope and pop on leaving it,
+    // however the scope is left (including an early return or ?).
+    pub fn push_scoped(&mut self, data: T) -> PushGuard<'_, T> {
+        self.push(data);
+        PushGuard { stack: self }
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        // Start at the top node; as_deref() turns &Option<Box<StackNode<T>>> into Option<&StackNode<T>>.
+        Iter { next: self.top.as_deref(), remaining: self.len }
+    }
+}
+
+// The compiler-generated drop for Option<Box<StackNode<T>>> is recursive: dropping the top node
+// drops its 'next' box, which drops its 'next' box, and so on. A deep enough stack overflows the native stack.
+// clear() walks the chain iteratively instead.
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Deep-copies the node chain so the clone is independent of the original.
+// Cloning node-by-node recursively would overflow on deep stacks, so the elements are gathered
+// top-to-bottom into a temporary Vec and pushed back bottom-first, which rebuilds the same order.
+// The clone gets the same pool size, but starts with an empty pool and no on_event() callback
+// (a boxed closure can't be cloned).
+impl<T: Clone> Clone for Stack<T> {
+    fn clone(&self) -> Self {
+        let elements: Vec<&T> = self.iter().collect();
+        let mut stack = Stack::with_pool(self.max_free);
+        stack.extend(elements.into_iter().rev().cloned());
+        stack
+    }
+}
+
+// Two stacks are equal when they hold equal elements in the same top-to-bottom order.
+// Lengths are compared first (an O(1) check), then both chains are walked side by side.
+impl<T: PartialEq> PartialEq for Stack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Stack<T> {}
+
+// Hashes the length followed by every element top-to-bottom, so stacks that compare equal hash equally.
+impl<T: Hash> Hash for Stack<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for data in self.iter() {
+            data.hash(state);
+        }
+    }
+}
+
+// Formats the stack as [top, ..., bottom], e.g. [3, 2, 1], using each element's Debug output.
+impl<T: fmt::Debug> fmt::Debug for Stack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Formats the stack as [top, ..., bottom], e.g. [3, 2, 1], using each element's Display output.
+impl<T: fmt::Display> fmt::Display for Stack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, data) in self.iter().enumerate() {
+            // Separate elements with ", " but don't put one in front of the top element.
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", data)?;
+        }
+        write!(f, "]")
+    }
+}
+
+// Build a stack by pushing each item of the iterator in order.
+// Because every push goes on top, the LAST item of the iterator ends up on top of the stack:
+// (0..5).collect::<Stack<_>>() has 4 on top and 0 at the bottom.
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = Stack::new();
+        stack.extend(iter);
+        stack
+    }
+}
+
+// Push each item of the iterator onto the stack in order, so the last item ends up on top.
+impl<T> Extend<T> for Stack<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.push_iter(iter);
+    }
+}
+
+// Push the Vec's elements in order, so its last element ends up on top (as if it had been used as a stack).
+impl<T> From<Vec<T>> for Stack<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+// Drain the stack top first, so the top ends up at index 0. Note that this is the reverse of the Vec the
+// stack may have been built from: Vec::from(Stack::from(v)) is v reversed.
+impl<T> From<Stack<T>> for Vec<T> {
+    fn from(stack: Stack<T>) -> Self {
+        stack.into_vec()
+    }
+}
+
+// A consuming iterator over a Stack, created by calling into_iter() (or by using a Stack in a for loop).
+// It owns the stack and pops one element per call to next(), so elements come out in LIFO order.
+pub struct IntoIter<T>(Stack<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+    // The stack's len goes down with every pop, so it is always exactly the number of elements left.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+// A draining iterator over a Stack, created by calling drain(). Each next() pops the top element.
+pub struct Drain<'a, T> {
+    stack: &'a mut Stack<T>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.stack.pop()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.stack.len(), Some(self.stack.len()))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+// A draining iterator over a Stack that yields Vecs of popped elements, created by calling drain_chunks().
+// As with Drain, anything not yet drained when it is dropped stays on the stack.
+pub struct DrainChunks<'a, T> {
+    stack: &'a mut Stack<T>,
+    chunk_size: usize,
+}
+
+impl<T> Iterator for DrainChunks<'_, T> {
+    type Item = Vec<T>;
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.stack.is_empty() {
+            return None;
+        }
+        Some(self.stack.pop_n(self.chunk_size))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let chunks = self.stack.len().div_ceil(self.chunk_size);
+        (chunks, Some(chunks))
+    }
+}
+
+impl<T> ExactSizeIterator for DrainChunks<'_, T> {}
+
+// Created by push_scoped(). It borrows the stack mutably for as long as it lives, so nothing else can
+// push or pop in the meantime and the value it pushed is still the top element when it is dropped.
+// It derefs to the Stack for read-only access (e.g. len() as the nesting depth).
+pub struct PushGuard<'a, T> {
+    stack: &'a mut Stack<T>,
+}
+
+impl<T> PushGuard<'_, T> {
+    // Keep the pushed value on the stack instead of popping it when the guard goes away.
+    pub fn commit(self) {
+        // Skipping the guard's Drop is all committing takes; the guard owns nothing but the borrow.
+        mem::forget(self);
+    }
+}
+
+impl<T> core::ops::Deref for PushGuard<'_, T> {
+    type Target = Stack<T>;
+    fn deref(&self) -> &Stack<T> {
+        self.stack
+    }
+}
+
+impl<T> Drop for PushGuard<'_, T> {
+    fn drop(&mut self) {
+        self.stack.pop();
+    }
+}
+
+// A borrowing iterator over a Stack, created by calling iter().
+// It follows the 'next' pointers from the top node down and yields a reference to each element,
+// leaving the stack untouched. Since it only needs &Stack<T>, it can be used through a MutexGuard.
+pub struct Iter<'a, T> {
+    next: Option<&'a StackNode<T>>,
+    // Elements not yet yielded, starting from the stack's len, so the iterator can report its exact length.
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            // Move on to the node below; the bottom node's 'next' is None, which ends the iteration.
+            self.next = node.next.as_deref();
+            self.remaining -= 1;
+            &node.data
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
@@ -0,0 +1,124 @@
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+use crossbeam_epoch::{self as epoch, Atomic, Owned};
+
+// A node of the lock-free stack. The data is wrapped in ManuallyDrop because pop() moves it out
+// with ptr::read while the node itself is only freed later, once no other thread can still be reading it.
+struct Node<T> {
+    data: ManuallyDrop<T>,
+    next: Atomic<Node<T>>,
+}
+
+// A Treiber stack: instead of locking, push and pop read the head pointer, prepare the change,
+// and publish it with a single compare-and-swap, retrying if another thread moved the head in between.
+//
+// Popped nodes can't be freed straight away because other threads may still be looking at them,
+// and reusing their memory too early is what causes the ABA problem. crossbeam-epoch's Atomic
+// (an epoch-managed atomic pointer) defers freeing a node until every thread that could have seen it has moved on.
+pub struct LockFreeStack<T> {
+    head: Atomic<Node<T>>,
+    // A running count of the elements, for approx_len(). push() adds one before its node is published and pops
+    // subtract one only after unlinking a node, so the count can run ahead of the stack but never below it
+    // (and never wraps around below zero).
+    len: AtomicUsize,
+}
+
+impl<T> LockFreeStack<T> {
+    pub fn new() -> Self {
+        Self { head: Atomic::null(), len: AtomicUsize::new(0) }
+    }
+    pub fn push(&self, data: T) {
+        let mut node = Owned::new(Node { data: ManuallyDrop::new(data), next: Atomic::null() });
+        self.len.fetch_add(1, Relaxed);
+        // Pinning the current thread keeps any node it loads alive until the guard is dropped.
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Relaxed, &guard);
+            // Point the new node at the current head, then try to swing the head over to the new node.
+            node.next.store(head, Relaxed);
+            match self.head.compare_exchange(head, node, Release, Relaxed, &guard) {
+                Ok(_) => return,
+                // Another thread changed the head first; take the node back and try again.
+                Err(err) => node = err.new,
+            }
+        }
+    }
+    pub fn pop(&self) -> Option<T> {
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Acquire, &guard);
+            // Safety: the node can't be freed while this thread is pinned.
+            let node = unsafe { head.as_ref() }?;
+            let next = node.next.load(Relaxed, &guard);
+            // Only take the head if it is still the node we just read.
+            if self.head.compare_exchange(head, next, Relaxed, Relaxed, &guard).is_ok() {
+                self.len.fetch_sub(1, Relaxed);
+                // Safety: the successful CAS unlinked the node, so this is the only thread that will
+                // read its data, and defer_destroy frees it only once no pinned thread can see it.
+                unsafe {
+                    guard.defer_destroy(head);
+                    return Some(ManuallyDrop::into_inner(ptr::read(&node.data)));
+                }
+            }
+        }
+    }
+    // Pop the head only if it equals 'expected', otherwise leave the stack alone and return None. The check and
+    // the pop are one step: the CAS only succeeds if the head is still the node whose value was compared, so a
+    // value pushed or popped by another thread in between can't be popped by mistake. If the head did change,
+    // the new head is compared instead.
+    //
+    // T has to be Copy because the value is compared before this thread owns it: another thread can pop the same
+    // node and drop its (moved-out) value in the meantime. The node's memory stays valid while this thread is
+    // pinned, but a value owning other memory (a String's buffer, say) could already have freed it. A Copy value
+    // owns nothing else, so its bytes in the node are always safe to read.
+    pub fn pop_if_head_is(&self, expected: &T) -> Option<T>
+    where
+        T: PartialEq + Copy,
+    {
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Acquire, &guard);
+            // Safety: the node can't be freed while this thread is pinned.
+            let node = unsafe { head.as_ref() }?;
+            if *node.data != *expected {
+                return None;
+            }
+            let next = node.next.load(Relaxed, &guard);
+            if self.head.compare_exchange(head, next, Relaxed, Relaxed, &guard).is_ok() {
+                self.len.fetch_sub(1, Relaxed);
+                // Safety: as in pop(), the successful CAS gave this thread sole ownership of the node's data.
+                unsafe {
+                    guard.defer_destroy(head);
+                    return Some(ManuallyDrop::into_inner(ptr::read(&node.data)));
+                }
+            }
+        }
+    }
+    // Roughly how many elements the stack holds, read with a single atomic load that never waits on other threads.
+    // It is only eventually consistent, not a snapshot: while pushes and pops are in flight it can count elements
+    // that aren't on the stack yet (it is never lower than the true length), and by the time it is returned other
+    // threads may have changed the stack anyway. Once they are all done it is exact.
+    pub fn approx_len(&self) -> usize {
+        self.len.load(Relaxed)
+    }
+    pub fn is_empty(&self) -> bool {
+        let guard = epoch::pin();
+        self.head.load(Acquire, &guard).is_null()
+    }
+}
+
+impl<T> Default for LockFreeStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for LockFreeStack<T> {
+    fn drop(&mut self) {
+        // Pop (and drop) every remaining element so their data and nodes are freed, one node at a time.
+        while self.pop().is_some() {}
+    }
+}
@@ -1,124 +1,305 @@
-use std::thread;
-use std::sync::{Mutex, Arc};
-use std::fs::{File};
-use std::io::{Write, BufWriter};
+use std::env;
+use std::fmt::Display;
+use std::process;
+use std::sync::Arc;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use synchronization_thread_safe_stack::{ConcurrentStack, StackError};
+use synchronization_thread_safe_stack::demo::{merge_files, remaining_sum, replay, run_work_queue, spawn_together, test_stack_with_options, Checksum, FlushOnDrop, LogFormat, MAX_ITERATIONS, PeriodicFlush, RunOptions, TaggedWriter, Task, ThreadStats};
 
-// A Box is needed (allocates memory on the heap) to avoid recursive types.
-struct StackNode<T> {
-    data: T,
-    next: Option<Box<StackNode<T>>>,
+const USAGE: &str = "Usage: synchronization_thread_safe_stack [--threads N] [--iterations M] [--format text|json] [--payload int|string|task] [--merge | --shared] [--replay] [--flush-every N] [--quiet-empty] [--timing] [--trace] [--work-queue]";
+
+// Settings for a demo run, filled in from the command line.
+struct Config {
+    // Number of threads to spawn.
+    threads: usize,
+    // Number of loop iterations each thread runs in test_stack.
+    iterations: i32,
+    // Whether operations are logged as plain text or as JSON lines.
+    format: LogFormat,
+    // Whether the per-thread output files are concatenated into 'output.txt' at the end.
+    merge: bool,
+    // Whether every thread writes to one shared 'output.txt', each line tagged with the thread's index.
+    shared: bool,
+    // The type of value pushed onto the stack.
+    payload: Payload,
+    // Flush each thread's log after this many lines, so most of it is on disk if the program crashes.
+    // None leaves it to the BufWriter, which writes when its buffer fills and when the thread finishes.
+    flush_every: Option<usize>,
+    // Whether empty pops are only counted, with one summary line per thread, instead of each getting a log line.
+    quiet_empty: bool,
+    // Whether each thread times its pushes and pops and logs their latency percentiles at the end.
+    timing: bool,
+    // Whether pushes and pops are reported as tracing events on stderr instead of being logged to files.
+    // Needs the "tracing" feature.
+    trace: bool,
+    // Whether to run the work-queue demo (threads handing tasks and results between two stacks) instead.
+    work_queue: bool,
+    // Whether to replay every thread's operations one after another on the main thread instead of spawning threads.
+    replay: bool,
 }
 
-struct Stack<T> {
-    top: Option<Box<StackNode<T>>>,
+// The element types the demo can run the stack with, to show Stack<T> isn't tied to integers.
+#[derive(Clone, Copy)]
+enum Payload {
+    // i32 values, as in the original demo.
+    Int,
+    // The same numbers as Strings.
+    String,
+    // The same numbers wrapped in the demo's Task struct.
+    Task,
 }
 
-impl<T> Stack<T> {
-    fn new() -> Self {
-        Self { top: None }
-    }
-    fn push(&mut self, data: T) {
-        // Create a new pointer to a StackNode and set its 'next' pointer to the
-        // take() takes the value out of the option, leaving a None in its place.
-        // This effectively removes the current top node in the stack,
-        // relocating it to the 'next' pointer in the new_node.
-        let new_node = Box::new(StackNode {
-            data,
-            next: self.top.take()
-        });
-        // Since we used take() and left a None value at the top of the stack, we need to update it
-        // Set the new top pointer (currently None since we took it) to the new_node
-        self.top = Some(new_node);
-    }
-    fn pop(&mut self) -> Option<T> {
-        // Unlike .take(), .map() DOES unwrap the Option, so we are just dealing with a Box<StackNode<T>>.
-        // Box acts like a reference, so we can access the fields of the StackNode inside with '.'
-        self.top.take().map(|node| {
-            let ret = node.data;
-            // Set the new top of the stack to the next node it pointed to
-            self.top = node.next;
-            ret
-        })
+// Parse '--threads N', '--iterations M', '--format text|json', '--payload int|string|task', '--merge', '--shared',
+// '--replay', '--flush-every N', '--quiet-empty', '--timing', '--trace' and '--work-queue' from the command line
+// arguments (program name excluded), falling back to 200 threads, 500 iterations, text logs, i32 values, a file per
+// thread without merging, real threads, no periodic flushing, logging every empty pop, no timing, file logs and the
+// push/pop demo when a flag is not given.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut config = Config {
+        threads: 200,
+        iterations: 500,
+        format: LogFormat::Text,
+        merge: false,
+        shared: false,
+        payload: Payload::Int,
+        flush_every: None,
+        quiet_empty: false,
+        timing: false,
+        trace: false,
+        work_queue: false,
+        replay: false,
+    };
+    while let Some(flag) = args.next() {
+        // Switches just turn their setting on; every other flag takes the argument after it as its value.
+        match flag.as_str() {
+            "--merge" => config.merge = true,
+            "--shared" => config.shared = true,
+            "--replay" => config.replay = true,
+            "--quiet-empty" => config.quiet_empty = true,
+            "--timing" => config.timing = true,
+            "--trace" => config.trace = true,
+            "--work-queue" => config.work_queue = true,
+            "--threads" => config.threads = parse_positive(&flag, &value_of(&mut args, &flag)?)?,
+            "--iterations" => config.iterations = parse_positive(&flag, &value_of(&mut args, &flag)?)?,
+            "--format" => config.format = parse_format(&value_of(&mut args, &flag)?)?,
+            "--payload" => config.payload = parse_payload(&value_of(&mut args, &flag)?)?,
+            "--flush-every" => config.flush_every = Some(parse_positive(&flag, &value_of(&mut args, &flag)?)?),
+            _ => return Err(format!("Unknown argument {}", flag)),
+        }
+    }
+    // Each iteration pushes values up to three times its index, which have to fit in the i32 payload.
+    if config.iterations > MAX_ITERATIONS {
+        return Err(format!("--iterations can be at most {}, got {}", MAX_ITERATIONS, config.iterations));
+    }
+    // JSON lines write values unquoted, which is only valid JSON for numbers.
+    if config.format == LogFormat::Json && !matches!(config.payload, Payload::Int) {
+        return Err("--format json only supports --payload int".to_string());
+    }
+    // There are no per-thread files to merge when every thread writes to the same one.
+    if config.merge && config.shared {
+        return Err("--merge and --shared can't be used together".to_string());
+    }
+    // The [T{n}] tags would make the JSON lines invalid; they carry a "thread" field instead.
+    if config.shared && config.format == LogFormat::Json {
+        return Err("--shared only supports --format text".to_string());
     }
+    if config.trace && !cfg!(feature = "tracing") {
+        return Err("--trace needs the program to be built with --features tracing".to_string());
+    }
+    // With --trace there are no log files to merge or share.
+    if config.trace && (config.merge || config.shared) {
+        return Err("--trace can't be used with --merge or --shared".to_string());
+    }
+    // Timings differ from run to run, which would break the replay's identical logs.
+    if config.replay && config.timing {
+        return Err("--timing can't be used with --replay".to_string());
+    }
+    Ok(config)
 }
 
-fn main() {
-    // Create and open a new file called 'output.txt', or exit if the file can't be created.
-    let file = File::create("output.txt").expect("Failed to create output file.");
-    // Wrap the file in a BufWriter for efficient writing.
-    let writer = BufWriter::new(file);
-    // Wrap the BufWriter in an Arc and Mutex to allow safe shared access across threads.
-    let shared_writer = Arc::new(Mutex::new(writer));
-    // Create a new stack instance, wrap it in an Arc and Mutex for thread-safe shared access.
-    let shared_stack = Arc::new(Mutex::new(Stack::<i32>::new()));
-    // Initialize a vector to hold the handles of the spawned threads.
-    let mut handles = vec![];
-    // Loop 200 times to create 200 threads.
-    for _ in 0..200 {
-        // Clone the Arc pointing to the stack to pass to the thread.
-        let stack_clone = Arc::clone(&shared_stack);
-        // Clone the Arc pointing to the writer to pass to the thread.
-        let writer_clone = Arc::clone(&shared_writer);
-        // Spawn a new thread.
-        let handle = thread::spawn(move || {
-            // Lock the stack for this thread, panicking if the lock fails.
-            let mut stack = stack_clone.lock().unwrap();
-            // Lock the writer for this thread, panicking if the lock fails.
-            let mut writer = writer_clone.lock().unwrap();
-            // Execute the test_stack function which performs operations on the stack and writes to the file.
-            test_stack(&mut stack, &mut writer);
-        });
-        // Store the handle of the spawned thread in the vector.
-        handles.push(handle);
-    }
-    // After all threads are created, wait for each to complete.
-    for handle in handles {
-        // Block the current thread until the thread represented by handle completes.
-        handle.join().unwrap();
-    }
-    // Print to the console when all threads have completed their execution.
-    println!("Program completed.");
+// Grab the value that follows 'flag' on the command line.
+fn value_of(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("Missing value for {}", flag))
+}
 
+// Parse a flag's value, rejecting anything that is not an integer greater than zero.
+fn parse_positive<N: std::str::FromStr + PartialOrd + Default>(flag: &str, value: &str) -> Result<N, String> {
+    match value.parse::<N>() {
+        Ok(n) if n > N::default() => Ok(n),
+        _ => Err(format!("{} expects a positive integer, got '{}'", flag, value)),
+    }
 }
 
-// Define the test_stack function that operates on a mutable reference to a Stack of i32 and a mutable BufWriter for a File.
-fn test_stack(stack: &mut Stack<i32>, writer: &mut BufWriter<File>) {
-    // Iterate 500 times, using `i` as the loop counter.
-    for i in 0..500 {
-        // 3 intermixed push and pop operations
-        // 'i * 3 + _' is a way to generate distinct values for each iteration of the loop that are evenly spaced apart
-        let next_value1 = i * 3 + 1;
-        writeln!(writer, "Pushing {}", next_value1).expect("Error writing to file");
-        stack.push(next_value1);
+fn parse_format(value: &str) -> Result<LogFormat, String> {
+    match value {
+        "text" => Ok(LogFormat::Text),
+        "json" => Ok(LogFormat::Json),
+        _ => Err(format!("--format expects 'text' or 'json', got '{}'", value)),
+    }
+}
 
-        let next_value2 = i * 3 + 2;
-        writeln!(writer, "Pushing {}", next_value2).expect("Error writing to file");
-        stack.push(next_value2);
+fn parse_payload(value: &str) -> Result<Payload, String> {
+    match value {
+        "int" => Ok(Payload::Int),
+        "string" => Ok(Payload::String),
+        "task" => Ok(Payload::Task),
+        _ => Err(format!("--payload expects 'int', 'string' or 'task', got '{}'", value)),
+    }
+}
 
-        pop_and_log(stack, writer);
+fn main() {
+    // Read the thread and iteration counts from the command line, or print the usage and exit on bad input.
+    let config = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    });
+    // Print the demo's debug events, each with its thread and value, to stderr.
+    #[cfg(feature = "tracing")]
+    if config.trace {
+        tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).with_writer(io::stderr).init();
+    }
+    if config.work_queue {
+        work_queue(&config);
+        return;
+    }
+    // Run the demo with the requested element type; each closure turns a generated number into a value to push.
+    match config.payload {
+        Payload::Int => run(&config, |n| n),
+        Payload::String => run(&config, |n| n.to_string()),
+        Payload::Task => run(&config, |n| Task { id: n, label: format!("value {}", n) }),
+    }
+}
 
-        let next_value3 = i * 3 + 3;
-        writeln!(writer, "Pushing {}", next_value3).expect("Error writing to file");
-        stack.push(next_value3);
+// Run the demo on a shared stack of T values, either with real threads or as a deterministic replay, and check the totals.
+fn run<T: Display + Checksum + Send + 'static>(config: &Config, make_value: fn(i32) -> T) {
+    // Create a new stack instance (which locks itself on every operation), already wrapped in an Arc for shared access.
+    let shared_stack = ConcurrentStack::<T>::new_shared();
+    let totals = if config.replay {
+        replay_on_main_thread(config, &shared_stack, make_value)
+    } else {
+        spawn_threads(config, &shared_stack, make_value)
+    };
+    // Every successful pop removed one pushed value, so whatever wasn't popped must still be on the stack.
+    let remaining = shared_stack.lock().unwrap().len() as u64;
+    println!("Pushes: {}, pops: {}, empty pops: {}, left on stack: {}", totals.pushes, totals.pops, totals.empty_pops, remaining);
+    assert_eq!(totals.pushes - totals.pops, remaining, "pushes minus pops does not match the final stack size");
+    // Equal counts could still hide a lost value and a duplicated one; the sums of the values would not match then.
+    let remaining_sum = remaining_sum(&shared_stack).unwrap();
+    println!("Pushed sum: {}, popped sum: {}, sum left on stack: {}", totals.pushed_sum, totals.popped_sum, remaining_sum);
+    assert_eq!(totals.pushed_sum - totals.popped_sum, remaining_sum, "a value was lost or duplicated");
+    // Print to the console when all threads have completed their execution.
+    println!("Program completed.");
+}
 
-        pop_and_log(stack, writer);
+// The work-queue demo: '--iterations' tasks shared out between '--threads' workers. Nothing is logged to files;
+// the per-worker counts and a check that every task produced exactly one result are printed instead.
+fn work_queue(config: &Config) {
+    let (handled, results) = run_work_queue(config.iterations, config.threads).unwrap_or_else(|err| {
+        eprintln!("Work queue failed: {}", err);
+        process::exit(1);
+    });
+    let mut results = results.into_inner().into_vec();
+    println!("Tasks handled per worker: {:?}", handled);
+    results.sort_by_key(|result| result.task_id);
+    let all_done = results.iter().map(|result| result.task_id).eq(1..=config.iterations);
+    assert!(all_done, "a task was lost or handled twice");
+    println!("All {} tasks produced exactly one result.", results.len());
+    println!("Program completed.");
+}
 
-        pop_and_log(stack, writer);
-    }
+// Run every thread's operations one after another on the main thread, logging to 'output.txt'.
+// No scheduling is involved, so the log is identical on every run and can be compared against a known-good copy.
+// --quiet-empty doesn't apply: run on its own, test_stack never pops more than it has pushed, so nothing is ever empty.
+fn replay_on_main_thread<T: Display + Checksum>(config: &Config, stack: &ConcurrentStack<T>, make_value: fn(i32) -> T) -> ThreadStats {
+    let result = (|| -> Result<ThreadStats, StackError> {
+        let log: Box<dyn Write> = if config.trace { Box::new(io::sink()) } else { Box::new(BufWriter::new(File::create("output.txt")?)) };
+        let mut writer = FlushOnDrop::new(with_flushing(log, config.flush_every));
+        let stats = replay(stack, &mut writer, config.threads, config.iterations, config.format, make_value)?;
+        writer.flush()?;
+        Ok(stats)
+    })();
+    result.unwrap_or_else(|err| {
+        eprintln!("Replay failed: {}", err);
+        process::exit(1);
+    })
 }
 
-// Define a generic function pop_and_log that accepts a stack and a writer.
-// The generic type T must implement the 'Display' trait for formatting.
-fn pop_and_log<T: std::fmt::Display>(stack: &mut Stack<T>, writer: &mut BufWriter<File>) {
-    // Attempt to pop a value from the stack.
-    if let Some(value) = stack.pop() {
-        // If a value is successfully popped (i.e., the stack was not empty), write a log message stating the popped value.
-        writeln!(writer, "Popped {}", value).expect("Error writing to file");
+// Spawn the demo threads on the shared stack, wait for them, and add up their counts.
+fn spawn_threads<T: Display + Checksum + Send + 'static>(config: &Config, shared_stack: &Arc<ConcurrentStack<T>>, make_value: fn(i32) -> T) -> ThreadStats {
+    // By default each thread writes to its own 'output_{index}.txt', so threads never wait on each other for file I/O
+    // and the run measures contention on the stack rather than on a shared writer.
+    let paths: Vec<PathBuf> = (0..config.threads).map(|index| PathBuf::from(format!("output_{}.txt", index))).collect();
+    // With --shared they all write to one 'output.txt' instead, opened for appending so every thread's writes
+    // land at the end of the file. Each thread gets its own handle to it rather than sharing a locked writer.
+    let shared_file = if config.shared {
+        File::create("output.txt").expect("Failed to create output.txt.");
+        Some(OpenOptions::new().append(true).open("output.txt").expect("Failed to open output.txt."))
     } else {
-        // If no value could be popped (i.e., the stack was empty), write a log message stating that the stack was empty.
-        writeln!(writer, "Stack was empty, nothing to pop").expect("Error writing to file");
+        None
+    };
+    // Clone the Arc pointing to the stack to pass to the threads.
+    let stack_clone = Arc::clone(shared_stack);
+    let thread_paths = paths.clone();
+    let iterations = config.iterations;
+    let format = config.format;
+    let flush_every = config.flush_every;
+    let trace = config.trace;
+    let options = RunOptions { quiet_empty: config.quiet_empty, timing: config.timing };
+    // Spawn one thread per requested thread (200 by default); the index is the thread's sequence number in the log.
+    // Any error (I/O or a poisoned lock) ends the thread cleanly and is handed back through the join handle.
+    let handles = spawn_together(config.threads, move |index, start| -> Result<ThreadStats, StackError> {
+        // Either tag this thread's lines in the shared log, or create this thread's own output file
+        // and wrap it in a BufWriter for efficient writing.
+        // With --trace the log lines are dropped; the tracing events take their place.
+        let writer = match &shared_file {
+            _ if trace => Ok(Box::new(io::sink()) as Box<dyn Write>),
+            Some(file) => file.try_clone().map(|file| Box::new(TaggedWriter::new(file, index)) as Box<dyn Write>),
+            None => File::create(&thread_paths[index]).map(|file| Box::new(BufWriter::new(file)) as Box<dyn Write>),
+        };
+        // Wait until every thread has its writer, so they all start pushing and popping at once. This comes before
+        // checking for an error, since a thread that returned early would leave the others waiting forever.
+        start.wait();
+        // FlushOnDrop gets whatever was logged to the file even if the thread panics part-way through.
+        let mut writer = FlushOnDrop::new(with_flushing(writer?, flush_every));
+        // Execute the test_stack function which performs operations on the stack and writes to the file,
+        // handing its counts back through the join handle.
+        let stats = test_stack_with_options(&stack_clone, &mut writer, iterations, format, options, make_value)?;
+        // Flush explicitly: dropping a BufWriter silently ignores errors from the final write.
+        writer.flush()?;
+        Ok(stats)
+    });
+    // After all threads are created, wait for each to complete and add up their counts.
+    let mut totals = ThreadStats::default();
+    let mut failed = 0;
+    for (index, handle) in handles.into_iter().enumerate() {
+        // Block the current thread until the thread represented by handle completes.
+        match handle.join().unwrap() {
+            Ok(stats) => totals.merge(stats),
+            Err(err) => {
+                eprintln!("Thread {} failed: {}", index, err);
+                failed += 1;
+            }
+        }
+    }
+    // A failed thread's counts are lost, so the totals below can't be checked against the stack.
+    if failed > 0 {
+        eprintln!("{} of {} threads failed.", failed, config.threads);
+        process::exit(1);
+    }
+    // Optionally concatenate the per-thread files, in thread order, into a single 'output.txt'.
+    if config.merge {
+        merge_files(&paths, "output.txt").expect("Failed to merge output files.");
     }
+    totals
 }
 
-
-
+// Wrap a log writer so it is flushed every 'flush_every' lines, if the option was given.
+fn with_flushing(writer: Box<dyn Write>, flush_every: Option<usize>) -> Box<dyn Write> {
+    match flush_every {
+        Some(every) => Box::new(PeriodicFlush::new(writer, every)),
+        None => writer,
+    }
+}
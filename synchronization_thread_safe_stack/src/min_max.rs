@@ -0,0 +1,54 @@
+use crate::Stack;
+
+// A Stack that can report its smallest and largest element in O(1) after any push or pop.
+// Two auxiliary stacks hold the running minimums and maximums: a value is copied onto 'mins' when it is
+// less than or equal to the current minimum (and onto 'maxs' likewise), and copied off again when it is popped.
+// Equal values are pushed again so duplicates of the extremum are tracked correctly.
+pub struct MinMaxStack<T: Ord + Clone> {
+    values: Stack<T>,
+    mins: Stack<T>,
+    maxs: Stack<T>,
+}
+
+impl<T: Ord + Clone> MinMaxStack<T> {
+    pub fn new() -> Self {
+        Self { values: Stack::new(), mins: Stack::new(), maxs: Stack::new() }
+    }
+    pub fn push(&mut self, data: T) {
+        if self.mins.peek().is_none_or(|min| data <= *min) {
+            self.mins.push(data.clone());
+        }
+        if self.maxs.peek().is_none_or(|max| data >= *max) {
+            self.maxs.push(data.clone());
+        }
+        self.values.push(data);
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        let data = self.values.pop()?;
+        // If the popped value was the current extremum, the previous one takes over.
+        self.mins.pop_if(|min| *min == data);
+        self.maxs.pop_if(|max| *max == data);
+        Some(data)
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.values.peek()
+    }
+    pub fn min(&self) -> Option<&T> {
+        self.mins.peek()
+    }
+    pub fn max(&self) -> Option<&T> {
+        self.maxs.peek()
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<T: Ord + Clone> Default for MinMaxStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,51 @@
+use alloc::vec::Vec;
+
+use crate::Stack;
+
+// A Stack in which every element is no larger than the one below it (the values are non-increasing from the
+// bottom up, so the top is the smallest): pushing a value first pops every element smaller than it. This is the
+// building block of the classic "next greater element" algorithms: walking a sequence and calling
+// push_monotonic() on each value, the elements it evicts are exactly the ones whose next greater element is the
+// value being pushed.
+pub struct MonotonicStack<T: PartialOrd> {
+    stack: Stack<T>,
+}
+
+impl<T: PartialOrd> MonotonicStack<T> {
+    pub fn new() -> Self {
+        Self { stack: Stack::new() }
+    }
+    // Pop every element smaller than 'data', then push 'data'. Returns the evicted elements in the order
+    // they were popped (the top, i.e. the smallest, first). Equal elements stay, so the stack is non-increasing.
+    pub fn push_monotonic(&mut self, data: T) -> Vec<T> {
+        let mut evicted = Vec::new();
+        while let Some(smaller) = self.stack.pop_if(|top| *top < data) {
+            evicted.push(smaller);
+        }
+        self.stack.push(data);
+        evicted
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        self.stack.pop()
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.stack.peek()
+    }
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+    // The underlying Stack, for reading it (iter(), to_vec(), ...). It can't be modified through this,
+    // which could break the ordering.
+    pub fn as_stack(&self) -> &Stack<T> {
+        &self.stack
+    }
+}
+
+impl<T: PartialOrd> Default for MonotonicStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,69 @@
+use alloc::collections::BinaryHeap;
+use core::cmp::Ordering;
+
+// A stack-like container whose pop() returns the largest element instead of the most recently pushed one,
+// for schedulers that always take the most urgent job next. It keeps the push/pop/peek interface of Stack,
+// but stores the elements in a binary heap, so push and pop are O(log n) rather than O(1).
+// Among equal elements the most recently pushed one comes out first, as it would from a Stack.
+pub struct PriorityStack<T: Ord> {
+    heap: BinaryHeap<Entry<T>>,
+    // Incremented on every push and stored with the element, to order equal elements by recency.
+    pushes: u64,
+}
+
+// An element together with its push number. Ordered by the element first and then by the push number,
+// so the heap's maximum is the largest element and, among equal ones, the newest.
+struct Entry<T> {
+    data: T,
+    seq: u64,
+}
+
+impl<T: Ord> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Entry<T> {}
+
+impl<T: Ord> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.data.cmp(&other.data).then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl<T: Ord> PriorityStack<T> {
+    pub fn new() -> Self {
+        Self { heap: BinaryHeap::new(), pushes: 0 }
+    }
+    pub fn push(&mut self, data: T) {
+        self.heap.push(Entry { data, seq: self.pushes });
+        self.pushes += 1;
+    }
+    // Remove and return the largest element.
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|entry| entry.data)
+    }
+    // The element pop() would return next.
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|entry| &entry.data)
+    }
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+impl<T: Ord> Default for PriorityStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,26 @@
+use alloc::vec::Vec;
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::Stack;
+
+// A Stack is serialized as a sequence of its elements in top-to-bottom order,
+// so [3, 2, 1] in JSON is a stack with 3 on top.
+impl<T: Serialize> Serialize for Stack<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for data in self.iter() {
+            seq.serialize_element(data)?;
+        }
+        seq.end()
+    }
+}
+
+// Read the top-to-bottom sequence back and push it bottom-first, which restores the original order.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Stack<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elements = Vec::<T>::deserialize(deserializer)?;
+        Ok(elements.into_iter().rev().collect())
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{Stack, StackError};
+
+thread_local! {
+    // A number derived from the current thread's id, worked out once per thread, that picks its push shard.
+    static THREAD_HASH: usize = {
+        let mut hasher = DefaultHasher::new();
+        thread::current().id().hash(&mut hasher);
+        hasher.finish() as usize
+    };
+}
+
+// A concurrent stack split into N independently locked Stacks (shards), so threads pushing and popping at
+// the same time mostly take different locks instead of all queueing on one, as they do with ConcurrentStack.
+// Each thread pushes to the shard its thread id hashes to. pop() tries that same shard first, since in a thread
+// that pushes and pops it is where the newest elements are, then the others round-robin until one has an element.
+//
+// The price is ordering: each shard is LIFO, but the stack as a whole isn't. pop() returns the newest element
+// of whichever shard it finds first, not necessarily the newest element overall. pop() can also return None
+// while another thread is pushing to a shard it has already checked.
+pub struct ShardedStack<T, const N: usize> {
+    shards: [Mutex<Stack<T>>; N],
+    // Where the next pop() carries on after its own shard, so those pops are spread round-robin over the shards.
+    next_pop: AtomicUsize,
+}
+
+impl<T, const N: usize> ShardedStack<T, N> {
+    pub fn new() -> Self {
+        assert!(N > 0, "a ShardedStack needs at least one shard");
+        Self { shards: std::array::from_fn(|_| Mutex::new(Stack::new())), next_pop: AtomicUsize::new(0) }
+    }
+    // The shard the current thread pushes to.
+    fn own_shard(&self) -> usize {
+        THREAD_HASH.with(|hash| hash % N)
+    }
+    pub fn push(&self, data: T) -> Result<(), StackError> {
+        self.shards[self.own_shard()].lock()?.push(data);
+        Ok(())
+    }
+    // Pop from the first shard that has an element, trying every shard once. None means all of them were
+    // empty when they were checked.
+    pub fn pop(&self) -> Result<Option<T>, StackError> {
+        let own = self.own_shard();
+        if let Some(data) = self.shards[own].lock()?.pop() {
+            return Ok(Some(data));
+        }
+        let start = self.next_pop.fetch_add(1, Ordering::Relaxed);
+        for i in 0..N {
+            let shard = (start + i) % N;
+            if shard == own {
+                continue;
+            }
+            if let Some(data) = self.shards[shard].lock()?.pop() {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+    // The number of elements across all shards. The shards are locked one after another rather than all
+    // at once, so while other threads are pushing or popping this is only an estimate.
+    pub fn len(&self) -> Result<usize, StackError> {
+        let mut len = 0;
+        for shard in &self.shards {
+            len += shard.lock()?.len();
+        }
+        Ok(len)
+    }
+    pub fn is_empty(&self) -> Result<bool, StackError> {
+        Ok(self.len()? == 0)
+    }
+}
+
+impl<T, const N: usize> Default for ShardedStack<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,31 @@
+use synchronization_thread_safe_stack::{ArenaStack, Stack};
+
+#[test]
+fn push_pop_and_peek_behave_like_stack() {
+    let mut arena = ArenaStack::new();
+    let mut stack = Stack::new();
+    for i in 0..100 {
+        arena.push(i);
+        stack.push(i);
+        if i % 3 == 0 {
+            assert_eq!(arena.pop(), stack.pop());
+        }
+        assert_eq!(arena.peek(), stack.peek());
+        assert_eq!(arena.len(), stack.len());
+    }
+    assert!(arena.iter().eq(stack.iter()));
+    while let Some(value) = stack.pop() {
+        assert_eq!(arena.pop(), Some(value));
+    }
+    assert_eq!(arena.pop(), None);
+    assert!(arena.is_empty());
+}
+
+#[test]
+fn peek_mut_changes_the_top_element() {
+    let mut arena = ArenaStack::with_capacity(2);
+    arena.push(1);
+    arena.push(2);
+    *arena.peek_mut().unwrap() = 20;
+    assert_eq!(arena.iter().copied().collect::<Vec<_>>(), vec![20, 1]);
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use synchronization_thread_safe_stack::BlockingStack;
+
+#[test]
+fn pop_wait_blocks_until_a_producer_pushes() {
+    let stack = Arc::new(BlockingStack::new());
+
+    let consumer_stack = Arc::clone(&stack);
+    let consumer = thread::spawn(move || consumer_stack.pop_wait());
+
+    // Give the consumer time to start waiting on the empty stack before anything is pushed.
+    thread::sleep(Duration::from_millis(50));
+    let producer_stack = Arc::clone(&stack);
+    let producer = thread::spawn(move || producer_stack.push(42));
+
+    producer.join().unwrap();
+    assert_eq!(consumer.join().unwrap(), Some(42));
+}
+
+#[test]
+fn multiple_consumers_receive_every_element_exactly_once() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const ITEMS: usize = 1000;
+    let stack = Arc::new(BlockingStack::new());
+
+    // Start the consumers first so plenty of them are waiting when the pushes begin.
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            // Producers push PRODUCERS * ITEMS values in total, so each consumer takes an equal share.
+            thread::spawn(move || {
+                (0..PRODUCERS * ITEMS / CONSUMERS).map(|_| stack.pop_wait().unwrap()).collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    stack.push(p * ITEMS + i);
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    let mut consumed: Vec<usize> = consumers.into_iter().flat_map(|consumer| consumer.join().unwrap()).collect();
+    consumed.sort();
+    assert_eq!(consumed, (0..PRODUCERS * ITEMS).collect::<Vec<_>>());
+}
+
+#[test]
+fn pop_timeout_gives_up_when_nothing_is_pushed() {
+    let stack = BlockingStack::<i32>::new();
+    let start = Instant::now();
+    assert_eq!(stack.pop_timeout(Duration::from_millis(100)), None);
+    let waited = start.elapsed();
+    assert!(waited >= Duration::from_millis(100), "returned after only {:?}", waited);
+    assert!(waited < Duration::from_secs(5), "waited {:?}", waited);
+}
+
+#[test]
+fn pop_timeout_returns_a_value_pushed_while_waiting() {
+    let stack = Arc::new(BlockingStack::new());
+    assert_eq!(stack.pop_timeout(Duration::ZERO), None);
+    let producer_stack = Arc::clone(&stack);
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        producer_stack.push(7);
+    });
+    assert_eq!(stack.pop_timeout(Duration::from_secs(10)), Some(7));
+    producer.join().unwrap();
+}
+
+#[test]
+fn push_batch_wakes_a_consumer_per_item() {
+    const CONSUMERS: usize = 4;
+    let stack = Arc::new(BlockingStack::new());
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.pop_wait().unwrap())
+        })
+        .collect();
+    // Let the consumers start waiting before the batch arrives.
+    thread::sleep(Duration::from_millis(50));
+    stack.push_batch((0..100).collect());
+    let mut popped: Vec<i32> = consumers.into_iter().map(|consumer| consumer.join().unwrap()).collect();
+    popped.sort_unstable();
+    popped.dedup();
+    assert_eq!(popped.len(), CONSUMERS);
+    // Every other item is still on the stack.
+    let mut rest = Vec::new();
+    while let Some(value) = stack.pop_timeout(Duration::ZERO) {
+        rest.push(value);
+    }
+    assert_eq!(rest.len(), 100 - CONSUMERS);
+}
+
+#[test]
+fn push_batch_keeps_the_order_of_sequential_pushes() {
+    let stack = BlockingStack::new();
+    stack.push_batch((0..100).collect());
+    assert_eq!(stack.pop_wait(), Some(99));
+    assert_eq!(stack.pop_wait(), Some(98));
+}
+
+#[test]
+fn close_wakes_a_waiting_consumer_so_it_can_exit() {
+    let stack = Arc::new(BlockingStack::<i32>::new());
+    let consumer_stack = Arc::clone(&stack);
+    let consumer = thread::spawn(move || {
+        let mut popped = 0;
+        while consumer_stack.pop_wait().is_some() {
+            popped += 1;
+        }
+        popped
+    });
+    // Give the consumer time to start waiting on the empty stack before it is closed.
+    thread::sleep(Duration::from_millis(50));
+    stack.close();
+    assert_eq!(consumer.join().unwrap(), 0);
+    assert!(stack.is_closed());
+    assert_eq!(stack.pop_timeout(Duration::from_secs(10)), None);
+}
+
+#[test]
+fn consumers_drain_what_is_left_after_close() {
+    const CONSUMERS: usize = 4;
+    let stack = Arc::new(BlockingStack::new());
+    stack.push_batch((0..100).collect());
+    stack.close();
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                while let Some(value) = stack.pop_wait() {
+                    popped.push(value);
+                }
+                popped
+            })
+        })
+        .collect();
+    let mut popped: Vec<i32> = consumers.into_iter().flat_map(|consumer| consumer.join().unwrap()).collect();
+    popped.sort_unstable();
+    assert_eq!(popped, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn push_on_a_full_bounded_stack_waits_for_a_pop() {
+    let stack = Arc::new(BlockingStack::bounded(1));
+    stack.push(1);
+    let producer = {
+        let stack = Arc::clone(&stack);
+        thread::spawn(move || stack.push(2))
+    };
+    // Give the producer time to find the stack full and start waiting.
+    thread::sleep(Duration::from_millis(50));
+    assert!(!producer.is_finished());
+    assert_eq!(stack.pop_wait(), Some(1));
+    producer.join().unwrap();
+    assert_eq!(stack.pop_timeout(Duration::from_secs(10)), Some(2));
+}
+
+#[test]
+fn push_batch_larger_than_a_bounded_stack_completes_with_a_consumer() {
+    let stack = Arc::new(BlockingStack::bounded(4));
+    let consumer = {
+        let stack = Arc::clone(&stack);
+        thread::spawn(move || {
+            let mut popped = Vec::new();
+            while let Some(value) = stack.pop_wait() {
+                popped.push(value);
+            }
+            popped
+        })
+    };
+    stack.push_batch((0..100).collect());
+    stack.close();
+    let mut popped = consumer.join().unwrap();
+    popped.sort_unstable();
+    assert_eq!(popped, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn pop_timeout_with_duration_max_behaves_like_pop_wait() {
+    let stack = BlockingStack::new();
+    stack.push(1);
+    assert_eq!(stack.pop_timeout(Duration::MAX), Some(1));
+    stack.close();
+    assert_eq!(stack.pop_timeout(Duration::MAX), None);
+}
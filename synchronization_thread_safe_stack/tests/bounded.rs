@@ -0,0 +1,36 @@
+use synchronization_thread_safe_stack::BoundedStack;
+#[cfg(feature = "std")]
+use synchronization_thread_safe_stack::StackError;
+
+#[test]
+fn push_succeeds_up_to_capacity() {
+    let mut stack = BoundedStack::new(3);
+    for i in 0..3 {
+        assert_eq!(stack.push(i), Ok(()));
+    }
+    assert!(stack.is_full());
+    assert_eq!(stack.len(), 3);
+}
+
+#[test]
+fn push_beyond_capacity_returns_the_value() {
+    let mut stack = BoundedStack::new(2);
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.push(3), Err(3));
+    assert_eq!(stack.peek(), Some(&2));
+    // Popping frees a slot again.
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.push(3), Ok(()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn push_checked_reports_a_full_stack_as_capacity_exceeded() {
+    let mut stack = BoundedStack::new(1);
+    assert!(stack.push_checked(1).is_ok());
+    let err = stack.push_checked(2).unwrap_err();
+    assert!(matches!(err, StackError::CapacityExceeded));
+    assert_eq!(err.to_string(), "the stack is full");
+    assert_eq!(stack.len(), 1);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+// Runs the demo binary and reads its logs back, to check that every logged operation made it to disk.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const THREADS: usize = 8;
+const ITERATIONS: usize = 500;
+// Every iteration of test_stack logs three pushes and three pops.
+const LINES: usize = THREADS * ITERATIONS * 6;
+
+// An empty directory in the system temp directory for one run of the binary to write its logs into.
+fn run_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("stack_cli_{}_{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Run the binary in 'dir' with the given extra arguments and return the lines of the output.txt it leaves there.
+fn run_and_read_output(dir: &Path, args: &[&str]) -> String {
+    let status = Command::new(env!("CARGO_BIN_EXE_synchronization_thread_safe_stack"))
+        .args(["--threads", &THREADS.to_string(), "--iterations", &ITERATIONS.to_string()])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::read_to_string(dir.join("output.txt")).unwrap()
+}
+
+#[test]
+fn merged_log_holds_a_line_for_every_operation() {
+    let dir = run_dir("merge");
+    let output = run_and_read_output(&dir, &["--merge"]);
+    assert_eq!(output.lines().count(), LINES);
+    assert!(output.ends_with('\n'));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn shared_log_holds_a_line_for_every_operation() {
+    let dir = run_dir("shared");
+    let output = run_and_read_output(&dir, &["--shared"]);
+    assert_eq!(output.lines().count(), LINES);
+    // Each thread logged its share of the lines.
+    for thread in 0..THREADS {
+        let tag = format!("[T{}] ", thread);
+        assert_eq!(output.lines().filter(|line| line.starts_with(&tag)).count(), ITERATIONS * 6);
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn iterations_beyond_what_the_values_can_hold_are_rejected() {
+    let dir = run_dir("too_many_iterations");
+    let output = Command::new(env!("CARGO_BIN_EXE_synchronization_thread_safe_stack"))
+        .args(["--iterations", &(i32::MAX / 3 + 1).to_string()])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("--iterations can be at most 715827882"));
+    // It stopped before opening any logs.
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,271 @@
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+use std::thread;
+
+use synchronization_thread_safe_stack::{ConcurrentStack, Stack, StackError};
+
+#[test]
+fn push_and_pop_return_ok_on_a_healthy_lock() {
+    let stack = ConcurrentStack::new();
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.pop().unwrap(), Some(2));
+    assert_eq!(stack.pop().unwrap(), Some(1));
+    assert_eq!(stack.pop().unwrap(), None);
+}
+
+#[test]
+fn pushes_from_many_threads_are_all_kept() {
+    let stack = Arc::new(ConcurrentStack::new());
+    let handles: Vec<_> = (0..8)
+        .map(|t| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    stack.push(t * 1000 + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let stack = Arc::try_unwrap(stack).ok().unwrap().into_inner();
+    let mut values: Vec<i32> = stack.into_iter().collect();
+    values.sort();
+    assert_eq!(values, (0..8000).collect::<Vec<_>>());
+}
+
+#[test]
+fn try_pop_and_try_push_give_up_while_another_thread_holds_the_lock() {
+    let stack = Arc::new(ConcurrentStack::new());
+    stack.push(1).unwrap();
+
+    let guard = stack.lock().unwrap();
+    let other = Arc::clone(&stack);
+    let (popped, pushed) = thread::spawn(move || (other.try_pop(), other.try_push(2))).join().unwrap();
+    assert_eq!(popped, None);
+    assert_eq!(pushed, Err(2));
+    drop(guard);
+
+    assert_eq!(stack.try_push(2), Ok(()));
+    assert_eq!(stack.try_pop(), Some(Some(2)));
+    assert_eq!(stack.try_pop(), Some(Some(1)));
+    assert_eq!(stack.try_pop(), Some(None));
+}
+
+#[test]
+fn pop_n_takes_a_batch_from_the_top() {
+    let stack = ConcurrentStack::new();
+    for i in 0..5 {
+        stack.push(i).unwrap();
+    }
+    assert_eq!(stack.pop_n(2).unwrap(), vec![4, 3]);
+    assert_eq!(stack.pop().unwrap(), Some(2));
+}
+
+#[test]
+fn push_iter_pushes_the_whole_batch() {
+    let stack = ConcurrentStack::new();
+    stack.push_iter(0..10).unwrap();
+    let stack = stack.into_inner();
+    assert_eq!(stack.len(), 10);
+    assert_eq!(stack.peek(), Some(&9));
+}
+
+#[test]
+fn snapshots_taken_during_pushes_are_consistent() {
+    const COUNT: usize = 10_000;
+    let stack = Arc::new(ConcurrentStack::new());
+    let producer_stack = Arc::clone(&stack);
+    let producer = thread::spawn(move || {
+        for i in 0..COUNT {
+            producer_stack.push(i).unwrap();
+        }
+    });
+
+    let mut last_len = 0;
+    while last_len < COUNT {
+        let snapshot = stack.snapshot().unwrap();
+        // The producer only pushes, so the stack can only grow between snapshots...
+        assert!(snapshot.len() >= last_len);
+        // ...and every snapshot must be exactly the pushes made so far, newest on top.
+        assert!(snapshot.iter().rev().copied().eq(0..snapshot.len()));
+        last_len = snapshot.len();
+    }
+    producer.join().unwrap();
+}
+
+#[test]
+fn pop_if_checks_and_pops_under_one_lock() {
+    let stack = ConcurrentStack::new();
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.pop_if(|top| *top == 1).unwrap(), None);
+    assert_eq!(stack.pop_if(|top| *top == 2).unwrap(), Some(2));
+}
+
+#[test]
+fn a_panic_while_locked_is_reported_and_recoverable() {
+    let stack = Arc::new(ConcurrentStack::new());
+    stack.push(1).unwrap();
+    let other = Arc::clone(&stack);
+    // A predicate that panics does so while the lock is held, which poisons it.
+    let result = thread::spawn(move || {
+        let _ = other.pop_if(|_| panic!("predicate panicked"));
+    })
+    .join();
+    assert!(result.is_err());
+
+    // The poisoned lock is reported to the caller as a typed error...
+    assert!(matches!(stack.pop(), Err(StackError::Poisoned)));
+    assert!(matches!(stack.push(2), Err(StackError::Poisoned)));
+    // ...and lock() still lets the caller reach the stack through the PoisonError.
+    assert_eq!(stack.lock().unwrap_err().into_inner().peek(), Some(&1));
+    stack.clear_poison();
+    assert_eq!(stack.pop().unwrap(), Some(1));
+}
+
+#[test]
+fn new_shared_can_be_cloned_into_several_threads() {
+    let stack = ConcurrentStack::new_shared();
+    let handles: Vec<_> = (0..2)
+        .map(|value| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.push(value).unwrap())
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let mut values = stack.snapshot().unwrap();
+    values.sort();
+    assert_eq!(values, vec![0, 1]);
+}
+
+#[test]
+fn is_contended_reports_a_lock_held_by_another_thread() {
+    let stack = ConcurrentStack::<i32>::new_shared();
+    assert!(!stack.is_contended());
+    let guard = stack.lock().unwrap();
+    let other = Arc::clone(&stack);
+    assert!(thread::spawn(move || other.is_contended()).join().unwrap());
+    drop(guard);
+    assert!(!stack.is_contended());
+}
+
+#[test]
+fn pop_all_empties_the_stack_in_one_go() {
+    let stack = ConcurrentStack::new();
+    stack.push_iter(1..=3).unwrap();
+    assert_eq!(stack.pop_all().unwrap(), vec![3, 2, 1]);
+    assert_eq!(stack.pop().unwrap(), None);
+    stack.push(4).unwrap();
+    assert_eq!(stack.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn repeated_pop_all_while_producers_push_loses_nothing() {
+    const PRODUCERS: usize = 4;
+    const PER_PRODUCER: usize = 10_000;
+    let stack = ConcurrentStack::new_shared();
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..PER_PRODUCER {
+                    stack.push(p * PER_PRODUCER + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    let mut drained = Vec::new();
+    while !producers.iter().all(|producer| producer.is_finished()) {
+        drained.extend(stack.pop_all().unwrap());
+    }
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    drained.extend(stack.pop_all().unwrap());
+
+    drained.sort_unstable();
+    assert_eq!(drained, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+}
+
+#[test]
+fn push_batch_matches_sequential_pushes() {
+    let stack = ConcurrentStack::new();
+    stack.push_batch((0..100).collect()).unwrap();
+    let guard = stack.lock().unwrap();
+    assert_eq!(guard.len(), 100);
+    assert_eq!(guard.peek(), Some(&99));
+    assert_eq!(guard.to_vec(), (0..100).rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn into_receiver_sends_every_element_then_disconnects() {
+    let stack = ConcurrentStack::new_shared();
+    let producers: Vec<_> = (0..4)
+        .map(|p| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.push_iter((0..250).map(|i| (p, i % 5))).unwrap())
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let stack = Arc::try_unwrap(stack).ok().unwrap();
+    let expected = stack.lock().unwrap().to_multiset();
+
+    // Collecting ends only once the draining thread has dropped its Sender.
+    let received: Stack<(i32, i32)> = stack.into_receiver().into_iter().collect();
+    assert_eq!(received.len(), 1000);
+    assert_eq!(received.to_multiset(), expected);
+}
+
+#[test]
+fn into_receiver_comes_out_top_first() {
+    let stack = ConcurrentStack::new();
+    stack.push_iter(1..=3).unwrap();
+    let received: Vec<i32> = stack.into_receiver().iter().collect();
+    assert_eq!(received, vec![3, 2, 1]);
+}
+
+#[test]
+fn stats_count_pops_that_hit_and_miss() {
+    let stack = ConcurrentStack::new();
+    assert_eq!(stack.stats(), (0, 0));
+    stack.push(1).unwrap();
+    assert_eq!(stack.pop().unwrap(), Some(1));
+    assert_eq!(stack.pop().unwrap(), None);
+    assert_eq!(stack.stats(), (1, 1));
+    // try_pop() counts too, once it gets the lock.
+    assert_eq!(stack.try_pop(), Some(None));
+    assert_eq!(stack.stats(), (1, 2));
+}
+
+#[test]
+fn pop_with_len_reads_the_length_under_the_same_lock() {
+    let stack = ConcurrentStack::new();
+    stack.push_iter(1..=2).unwrap();
+    assert_eq!(stack.pop_with_len().unwrap(), Some((2, 1)));
+    assert_eq!(stack.pop_with_len().unwrap(), Some((1, 0)));
+    assert_eq!(stack.pop_with_len().unwrap(), None);
+    assert_eq!(stack.stats(), (2, 1));
+}
+
+#[test]
+fn stats_count_batch_pops_per_element() {
+    let stack = ConcurrentStack::new();
+    stack.push_iter(1..=5).unwrap();
+    assert_eq!(stack.pop_n(2).unwrap(), vec![5, 4]);
+    assert_eq!(stack.stats(), (2, 0));
+    assert_eq!(stack.pop_if(|&top| top > 3).unwrap(), None);
+    assert_eq!(stack.stats(), (2, 1));
+    assert_eq!(stack.pop_all().unwrap(), vec![3, 2, 1]);
+    assert_eq!(stack.stats(), (5, 1));
+    // Draining an empty stack is a single miss.
+    assert_eq!(stack.pop_all().unwrap(), Vec::<i32>::new());
+    assert_eq!(stack.stats(), (5, 2));
+}
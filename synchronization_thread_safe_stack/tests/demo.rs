@@ -0,0 +1,525 @@
+#![cfg(feature = "std")]
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use synchronization_thread_safe_stack::demo::{
+    merge_files, pop_and_log, producer_consumer, remaining_sum, replay, run_work_queue, spawn_together, test_stack, test_stack_quiet,
+    test_stack_with, test_stack_with_options, thread_number, work_queue_worker, Checksum, FlushOnDrop, LogFormat, PeriodicFlush, RunOptions, MAX_ITERATIONS,
+    TaggedWriter, Task, TaskResult, ThreadStats,
+};
+use synchronization_thread_safe_stack::{ConcurrentStack, StackError};
+
+// A path in the system temp directory that no other test (or test run) uses.
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("stack_demo_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn json_log_lines_parse_back_with_matching_counts() {
+    let path = temp_path("json_log.jsonl");
+    let mut writer = BufWriter::new(File::create(&path).unwrap());
+    let stack = ConcurrentStack::new();
+    test_stack(&stack, &mut writer, 10, LogFormat::Json).unwrap();
+    drop(writer);
+
+    let contents = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let (mut pushes, mut pops) = (0, 0);
+    for line in contents.lines() {
+        let event: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(event["thread"].to_string(), thread_number());
+        match event["op"].as_str().unwrap() {
+            "push" => pushes += 1,
+            "pop" => {
+                // A single thread pops only after pushing, so no pop should find the stack empty.
+                assert!(event["value"].is_i64());
+                pops += 1;
+            }
+            op => panic!("unexpected op {}", op),
+        }
+    }
+    assert_eq!(pushes, 30);
+    assert_eq!(pops, 30);
+    assert!(stack.into_inner().is_empty());
+}
+
+#[test]
+fn concurrent_runs_leave_exactly_the_net_pushes_on_the_stack() {
+    const THREADS: usize = 8;
+    let stack = Arc::new(ConcurrentStack::new());
+    let paths: Vec<PathBuf> = (0..THREADS).map(|i| temp_path(&format!("interleave_{}.txt", i))).collect();
+    let handles: Vec<_> = paths
+        .iter()
+        .cloned()
+        .map(|path| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                let mut writer = BufWriter::new(File::create(path).unwrap());
+                test_stack(&stack, &mut writer, 1000, LogFormat::Text).unwrap();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let (mut pushes, mut pops) = (0, 0);
+    for path in &paths {
+        let contents = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+        pushes += contents.lines().filter(|line| line.starts_with("Pushing")).count();
+        pops += contents.lines().filter(|line| line.starts_with("Popped")).count();
+    }
+    assert_eq!(pushes, THREADS * 3000);
+    let stack = Arc::try_unwrap(stack).ok().unwrap().into_inner();
+    assert_eq!(stack.len(), pushes - pops);
+}
+
+#[test]
+fn test_stack_can_log_into_an_in_memory_buffer() {
+    let stack = ConcurrentStack::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    test_stack(&stack, &mut buffer, 1, LogFormat::Text).unwrap();
+    let log = String::from_utf8(buffer).unwrap();
+    assert_eq!(log, "Pushing 1\nPushing 2\nPopped 2\nPushing 3\nPopped 3\nPopped 1\n");
+}
+
+#[test]
+fn pop_and_log_reports_an_empty_stack() {
+    let stack = ConcurrentStack::<i32>::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    assert!(!pop_and_log(&stack, &mut buffer, LogFormat::Text).unwrap());
+    assert_eq!(String::from_utf8(buffer).unwrap(), "Stack was empty, nothing to pop\n");
+}
+
+#[test]
+fn merge_files_concatenates_parts_in_order_and_removes_them() {
+    let parts = [temp_path("part_0.txt"), temp_path("part_1.txt")];
+    fs::write(&parts[0], "Pushing 1\n").unwrap();
+    fs::write(&parts[1], "Pushing 2\n").unwrap();
+    let merged = temp_path("merged.txt");
+    merge_files(&parts, &merged).unwrap();
+    assert_eq!(fs::read_to_string(&merged).unwrap(), "Pushing 1\nPushing 2\n");
+    assert!(parts.iter().all(|part| !part.exists()));
+    fs::remove_file(&merged).unwrap();
+}
+
+#[test]
+fn aggregated_thread_stats_match_the_final_stack() {
+    const THREADS: u64 = 8;
+    const ITERATIONS: i32 = 1000;
+    let stack = ConcurrentStack::new_shared();
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || test_stack(&stack, &mut std::io::sink(), ITERATIONS, LogFormat::Text))
+        })
+        .collect();
+    let mut totals = ThreadStats::default();
+    for handle in handles {
+        totals.merge(handle.join().unwrap().unwrap());
+    }
+    assert_eq!(totals.pushes, THREADS * ITERATIONS as u64 * 3);
+    // test_stack attempts exactly as many pops as pushes.
+    assert_eq!(totals.pops + totals.empty_pops, totals.pushes);
+    assert_eq!(totals.pushes - totals.pops, stack.lock().unwrap().len() as u64);
+}
+
+// A writer that accepts 'remaining' bytes and then fails every write, like a disk filling up.
+struct FailingWriter {
+    remaining: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(io::Error::other("disk full"));
+        }
+        let written = buf.len().min(self.remaining);
+        self.remaining -= written;
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn a_failing_writer_returns_an_error_instead_of_panicking() {
+    let stack = ConcurrentStack::new_shared();
+    let thread_stack = Arc::clone(&stack);
+    let result = thread::spawn(move || {
+        test_stack(&thread_stack, &mut FailingWriter { remaining: 100 }, 1000, LogFormat::Text)
+    })
+    .join()
+    .expect("test_stack panicked");
+    let err = result.unwrap_err();
+    assert!(matches!(err, StackError::Io(_)));
+    assert_eq!(err.to_string(), "disk full");
+    // The stack lock was never poisoned, so other threads can keep using it.
+    assert!(stack.push(1).is_ok());
+}
+
+#[test]
+fn test_stack_with_pushes_and_pops_strings() {
+    let stack = ConcurrentStack::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    let stats = test_stack_with(&stack, &mut buffer, 1, LogFormat::Text, |n| format!("value-{}", n)).unwrap();
+    assert_eq!(stats.pushes, 3);
+    assert_eq!(stats.pops, 3);
+    let log = String::from_utf8(buffer).unwrap();
+    assert!(log.starts_with("Pushing value-1\nPushing value-2\nPopped value-2\n"));
+
+    stack.push("left".to_string()).unwrap();
+    assert_eq!(stack.pop().unwrap(), Some("left".to_string()));
+}
+
+#[test]
+fn test_stack_with_accepts_a_custom_display_type() {
+    let stack = ConcurrentStack::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    test_stack_with(&stack, &mut buffer, 1, LogFormat::Text, |id| Task { id, label: "job".to_string() }).unwrap();
+    let log = String::from_utf8(buffer).unwrap();
+    assert!(log.starts_with("Pushing task #1 (job)\n"));
+}
+
+#[test]
+fn replay_matches_the_golden_log() {
+    let stack = ConcurrentStack::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    let stats = replay(&stack, &mut buffer, 2, 3, LogFormat::Text, |n| n).unwrap();
+    // Values 1..=9, pushed and popped twice over.
+    assert_eq!(stats, ThreadStats { pushes: 18, pops: 18, empty_pops: 0, pushed_sum: 90, popped_sum: 90 });
+    // Regenerate with `synchronization_thread_safe_stack --replay --threads 2 --iterations 3` if the sequence changes on purpose.
+    assert_eq!(String::from_utf8(buffer).unwrap(), include_str!("golden/replay.txt"));
+}
+
+#[test]
+fn a_poisoned_stack_stops_the_run_with_an_error() {
+    let stack = ConcurrentStack::new_shared();
+    stack.push(0).unwrap();
+    let other = Arc::clone(&stack);
+    // The predicate only runs (and panics, poisoning the lock) when there is a top element.
+    let _ = thread::spawn(move || {
+        let _ = other.pop_if(|_| panic!("poison the lock"));
+    })
+    .join();
+    let result = test_stack(&stack, &mut Vec::new(), 10, LogFormat::Text);
+    assert!(matches!(result, Err(StackError::Poisoned)));
+}
+
+// Open 'path' for appending, as the demo's --shared mode does, after emptying it.
+fn open_shared_log(path: &PathBuf) -> File {
+    File::create(path).unwrap();
+    OpenOptions::new().append(true).open(path).unwrap()
+}
+
+#[test]
+fn tagged_lines_in_a_shared_log_group_into_valid_per_thread_sequences() {
+    const THREADS: usize = 4;
+    const ITERATIONS: i32 = 200;
+    let path = temp_path("shared_tagged.txt");
+    let log = open_shared_log(&path);
+    let stack = ConcurrentStack::new_shared();
+    let handles: Vec<_> = (0..THREADS)
+        .map(|index| {
+            let stack = Arc::clone(&stack);
+            let mut writer = TaggedWriter::new(log.try_clone().unwrap(), index);
+            thread::spawn(move || test_stack(&stack, &mut writer, ITERATIONS, LogFormat::Text).unwrap())
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let log = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let mut by_thread: HashMap<usize, Vec<&str>> = HashMap::new();
+    for line in log.lines() {
+        let (tag, operation) = line.split_once("] ").expect("every line is tagged");
+        let index = tag.strip_prefix("[T").unwrap().parse().unwrap();
+        by_thread.entry(index).or_default().push(operation);
+    }
+    assert_eq!(by_thread.len(), THREADS);
+    for operations in by_thread.values() {
+        // Each iteration of test_stack is push, push, pop, push, pop, pop; a pop may find the stack empty.
+        assert_eq!(operations.len(), ITERATIONS as usize * 6);
+        for iteration in operations.chunks(6) {
+            for (operation, is_push) in iteration.iter().zip([true, true, false, true, false, false]) {
+                if is_push {
+                    assert!(operation.starts_with("Pushing "), "{}", operation);
+                } else {
+                    assert!(operation.starts_with("Popped ") || *operation == "Stack was empty, nothing to pop", "{}", operation);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn full_lines_from_cloned_file_handles_never_interleave() {
+    const THREADS: usize = 8;
+    const LINES: usize = 2000;
+    let path = temp_path("cloned_handles.txt");
+    let log = open_shared_log(&path);
+    let handles: Vec<_> = (0..THREADS)
+        .map(|index| {
+            let mut writer = TaggedWriter::new(log.try_clone().unwrap(), index);
+            thread::spawn(move || {
+                for line in 0..LINES {
+                    writeln!(writer, "line {} of a deliberately long-ish message to widen the window for overlap", line).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let log = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(log.lines().count(), THREADS * LINES);
+    // Every line is intact: one tag, then the message it was written with.
+    assert!(log.lines().all(|line| line.starts_with("[T") && line.matches("[T").count() == 1 && line.ends_with("overlap")));
+}
+
+#[test]
+fn tagged_writer_holds_back_a_partial_line() {
+    let path = temp_path("partial_line.txt");
+    let mut file_writer = TaggedWriter::new(File::create(&path).unwrap(), 7);
+    write!(file_writer, "Push").unwrap();
+    file_writer.flush().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    writeln!(file_writer, "ing 1").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[T7] Pushing 1\n");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn periodic_flush_persists_lines_before_the_writer_is_dropped() {
+    let path = temp_path("periodic_flush.txt");
+    let stack = ConcurrentStack::new();
+    // One test_stack iteration writes 6 lines, so flushing every 6 lines empties the buffer after each one.
+    let mut writer = PeriodicFlush::new(BufWriter::new(File::create(&path).unwrap()), 6);
+    test_stack(&stack, &mut writer, 2, LogFormat::Text).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 12);
+
+    // Without the periodic flush, the same lines are still sitting in the BufWriter at this point.
+    let mut buffered = BufWriter::new(File::create(&path).unwrap());
+    test_stack(&stack, &mut buffered, 2, LogFormat::Text).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    drop(buffered);
+    fs::remove_file(&path).unwrap();
+}
+
+// A log that, like a competing consumer, empties the stack whenever anything is written to it.
+// In each test_stack iteration that steals the first value pushed, so the iteration's last pop finds the stack empty.
+struct StealingWriter<'a> {
+    stack: &'a ConcurrentStack<i32>,
+    log: Vec<u8>,
+}
+
+impl Write for StealingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stack.pop_all().unwrap();
+        self.log.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn quiet_mode_counts_empty_pops_in_one_summary_line() {
+    let stack = ConcurrentStack::new();
+    let mut noisy = StealingWriter { stack: &stack, log: Vec::new() };
+    let stats = test_stack_with(&stack, &mut noisy, 3, LogFormat::Text, |n| n).unwrap();
+    assert_eq!(stats.empty_pops, 3);
+    let noisy = String::from_utf8(noisy.log).unwrap();
+    assert_eq!(noisy.lines().filter(|line| line.contains("empty")).count(), 3);
+
+    let mut quiet = StealingWriter { stack: &stack, log: Vec::new() };
+    let stats = test_stack_quiet(&stack, &mut quiet, 3, LogFormat::Text, |n| n).unwrap();
+    assert_eq!(stats.empty_pops, 3);
+    let quiet = String::from_utf8(quiet.log).unwrap();
+    assert!(!quiet.contains("Stack was empty"));
+    assert_eq!(quiet.lines().last(), Some("Empty pops not logged: 3"));
+    assert_eq!(quiet.lines().count(), noisy.lines().count() - 3 + 1);
+}
+
+#[test]
+fn pushed_minus_popped_sums_match_what_is_left_on_the_stack() {
+    const THREADS: usize = 8;
+    let stack = ConcurrentStack::new_shared();
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || test_stack(&stack, &mut io::sink(), 2000, LogFormat::Text).unwrap())
+        })
+        .collect();
+    let mut totals = ThreadStats::default();
+    for handle in handles {
+        totals.merge(handle.join().unwrap());
+    }
+    // Each thread pushes 1..=6000 once.
+    assert_eq!(totals.pushed_sum, THREADS as i64 * 6000 * 6001 / 2);
+    assert_eq!(totals.pushed_sum - totals.popped_sum, remaining_sum(&stack).unwrap());
+}
+
+#[test]
+fn checksums_of_the_demo_payloads() {
+    assert_eq!(5.checksum(), 5);
+    assert_eq!("12".to_string().checksum(), i64::from(b'1') + i64::from(b'2'));
+    assert_eq!(Task { id: 3, label: "job".to_string() }.checksum(), 3);
+}
+
+#[test]
+fn the_work_queue_turns_every_task_into_exactly_one_result() {
+    const TASKS: i32 = 5000;
+    let (handled, results) = run_work_queue(TASKS, 8).unwrap();
+    assert_eq!(handled.len(), 8);
+    assert_eq!(handled.iter().sum::<u64>(), TASKS as u64);
+
+    let mut results = results.into_inner().into_vec();
+    results.sort_by_key(|result| result.task_id);
+    let expected: Vec<TaskResult> =
+        (1..=TASKS).map(|id| TaskResult { task_id: id, value: i64::from(id) * i64::from(id) }).collect();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn a_work_queue_worker_stops_once_the_tasks_run_out() {
+    let tasks = ConcurrentStack::new();
+    tasks.push(Task { id: 3, label: "three".to_string() }).unwrap();
+    let results = ConcurrentStack::new();
+    assert_eq!(work_queue_worker(&tasks, &results).unwrap(), 1);
+    assert_eq!(results.pop_all().unwrap(), vec![TaskResult { task_id: 3, value: 9 }]);
+    assert_eq!(work_queue_worker(&tasks, &results).unwrap(), 0);
+}
+
+#[test]
+fn timing_adds_a_latency_summary_for_pushes_and_pops() {
+    let stack = ConcurrentStack::new();
+    let mut buffer = Vec::new();
+    let options = RunOptions { timing: true, ..RunOptions::default() };
+    let stats = test_stack_with_options(&stack, &mut buffer, 10, LogFormat::Text, options, |n| n).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    // 10 iterations of 6 operations, then the two summary lines.
+    assert_eq!(lines.len(), 62);
+    assert_eq!(stats.pushes + stats.pops + stats.empty_pops, 60);
+    assert!(lines[60].starts_with("Push latency: p50 "), "{}", lines[60]);
+    assert!(lines[60].ends_with(" over 30 operations"), "{}", lines[60]);
+    assert!(lines[61].starts_with("Pop latency: p50 "), "{}", lines[61]);
+    assert!(lines[61].ends_with(" over 30 operations"), "{}", lines[61]);
+
+    // Without the option there is no summary, exactly as with test_stack_with.
+    let mut untimed = Vec::new();
+    test_stack_with_options(&stack, &mut untimed, 10, LogFormat::Text, RunOptions::default(), |n| n).unwrap();
+    assert!(!String::from_utf8(untimed).unwrap().contains("latency"));
+}
+
+#[test]
+fn timing_summaries_are_json_lines_in_json_mode() {
+    let stack = ConcurrentStack::new();
+    let mut buffer = Vec::new();
+    let options = RunOptions { quiet_empty: true, timing: true };
+    test_stack_with_options(&stack, &mut buffer, 5, LogFormat::Json, options, |n| n).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    let summaries: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .filter(|line: &serde_json::Value| line["op"] == "latency")
+        .collect();
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0]["kind"], "push");
+    assert_eq!(summaries[1]["kind"], "pop");
+    for summary in &summaries {
+        assert_eq!(summary["count"], 15);
+        let percentile = |key: &str| summary[key].as_u64().unwrap();
+        assert!(percentile("p50_ns") <= percentile("p90_ns"));
+        assert!(percentile("p90_ns") <= percentile("p99_ns"));
+        assert!(percentile("p99_ns") <= percentile("max_ns"));
+    }
+}
+
+#[test]
+fn spawn_together_holds_every_thread_at_the_barrier_until_all_arrive() {
+    const THREADS: usize = 16;
+    let arrived = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&arrived);
+    let handles = spawn_together(THREADS, move |index, start| {
+        // Make the early threads arrive well before the later ones.
+        thread::sleep(Duration::from_millis(index as u64 * 5));
+        counter.fetch_add(1, Ordering::SeqCst);
+        start.wait();
+        // Nobody gets past the barrier until the last thread has arrived.
+        (index, counter.load(Ordering::SeqCst))
+    });
+    let seen: Vec<(usize, usize)> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    assert_eq!(seen, (0..THREADS).map(|index| (index, THREADS)).collect::<Vec<_>>());
+    assert_eq!(arrived.load(Ordering::SeqCst), THREADS);
+}
+
+// A writer that only hands what was written to 'flushed' when it is flushed, like a buffer that never
+// flushes itself.
+struct FlushOnlyWriter {
+    pending: Vec<u8>,
+    flushed: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Write for FlushOnlyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed.lock().unwrap().append(&mut self.pending);
+        Ok(())
+    }
+}
+
+#[test]
+fn flush_on_drop_keeps_the_log_of_a_thread_that_panics() {
+    let flushed = Arc::new(Mutex::new(Vec::new()));
+    let inner = FlushOnlyWriter { pending: Vec::new(), flushed: Arc::clone(&flushed) };
+    let result = thread::spawn(move || {
+        let stack = ConcurrentStack::new();
+        let mut writer = FlushOnDrop::new(inner);
+        test_stack(&stack, &mut writer, 2, LogFormat::Text).unwrap();
+        panic!("the thread panicked before flushing its log");
+    })
+    .join();
+    assert!(result.is_err());
+    let log = String::from_utf8(flushed.lock().unwrap().clone()).unwrap();
+    assert_eq!(log.lines().count(), 12);
+}
+
+#[test]
+fn producer_consumer_hands_every_item_to_exactly_one_consumer() {
+    // A capacity much smaller than the item count, so the producer has to wait for room over and over.
+    let report = producer_consumer(1_000, 3, 2);
+    assert_eq!(report.consumed.len(), 3);
+    assert_eq!(report.consumed.iter().sum::<u64>(), 1_000);
+    assert_eq!(report.sum, 1_000 * 1_001 / 2);
+}
+
+#[test]
+fn test_stack_rejects_more_iterations_than_the_values_can_hold() {
+    let stack = ConcurrentStack::new();
+    let mut log = Vec::new();
+    match test_stack(&stack, &mut log, MAX_ITERATIONS + 1, LogFormat::Text) {
+        Err(StackError::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidInput),
+        other => panic!("expected an InvalidInput error, got {:?}", other),
+    }
+    // Nothing was run.
+    assert!(log.is_empty());
+    assert_eq!(stack.pop().unwrap(), None);
+}
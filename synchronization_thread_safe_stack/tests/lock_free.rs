@@ -0,0 +1,147 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::thread;
+
+use synchronization_thread_safe_stack::LockFreeStack;
+
+#[test]
+fn single_threaded_push_and_pop_is_lifo() {
+    let stack = LockFreeStack::new();
+    assert!(stack.is_empty());
+    stack.push(1);
+    stack.push(2);
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.pop(), Some(1));
+    assert_eq!(stack.pop(), None);
+}
+
+#[test]
+fn concurrent_push_pop_pairs_lose_and_duplicate_nothing() {
+    const THREADS: usize = 8;
+    const PAIRS: usize = 10_000;
+    let stack = Arc::new(LockFreeStack::new());
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                let mut popped = Vec::with_capacity(PAIRS);
+                for i in 0..PAIRS {
+                    stack.push(t * PAIRS + i);
+                    // Every pop follows this thread's own push, so the stack can't be empty here.
+                    popped.push(stack.pop().expect("stack was empty after a push"));
+                }
+                popped
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for handle in handles {
+        for value in handle.join().unwrap() {
+            assert!(seen.insert(value), "value {} was popped twice", value);
+        }
+    }
+    assert_eq!(seen.len(), THREADS * PAIRS);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn dropping_the_stack_drops_remaining_elements() {
+    let value = Arc::new(());
+    let stack = LockFreeStack::new();
+    for _ in 0..10 {
+        stack.push(Arc::clone(&value));
+    }
+    drop(stack);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn pop_if_head_is_only_pops_a_matching_head() {
+    let stack = LockFreeStack::new();
+    assert_eq!(stack.pop_if_head_is(&1), None);
+    stack.push(1);
+    stack.push(2);
+    assert_eq!(stack.pop_if_head_is(&1), None);
+    assert_eq!(stack.pop_if_head_is(&2), Some(2));
+    assert_eq!(stack.pop_if_head_is(&1), Some(1));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn concurrent_pop_if_head_is_never_pops_someone_elses_value() {
+    const THREADS: usize = 8;
+    const PUSHES: usize = 10_000;
+    let stack = Arc::new(LockFreeStack::new());
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                for i in 0..PUSHES {
+                    let value = t * PUSHES + i;
+                    stack.push(value);
+                    // Succeeds only while no other thread has pushed on top of this thread's value.
+                    if let Some(got) = stack.pop_if_head_is(&value) {
+                        assert_eq!(got, value);
+                        popped.push(got);
+                    }
+                }
+                popped
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for handle in handles {
+        for value in handle.join().unwrap() {
+            assert!(seen.insert(value), "value {} was popped twice", value);
+        }
+    }
+    // Whatever wasn't taken back is still on the stack, exactly once.
+    while let Some(value) = stack.pop() {
+        assert!(seen.insert(value), "value {} was popped twice", value);
+    }
+    assert_eq!(seen.len(), THREADS * PUSHES);
+}
+
+#[test]
+fn approx_len_counts_single_threaded_pushes_and_pops_exactly() {
+    const N: usize = 1000;
+    let stack = LockFreeStack::new();
+    assert_eq!(stack.approx_len(), 0);
+    for i in 0..N {
+        stack.push(i);
+    }
+    assert_eq!(stack.approx_len(), N);
+    stack.pop();
+    stack.pop_if_head_is(&(N - 2));
+    assert_eq!(stack.approx_len(), N - 2);
+}
+
+#[test]
+fn approx_len_is_exact_once_concurrent_threads_are_done() {
+    const THREADS: usize = 8;
+    const PUSHES: usize = 10_000;
+    let stack = Arc::new(LockFreeStack::new());
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..PUSHES {
+                    stack.push(t * PUSHES + i);
+                    // Pop every other time, so pushes and pops overlap across threads.
+                    if i % 2 == 0 {
+                        stack.pop();
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(stack.approx_len(), THREADS * PUSHES / 2);
+}
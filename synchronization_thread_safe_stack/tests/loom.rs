@@ -0,0 +1,51 @@
+// Model-checked tests: loom runs each closure once per possible interleaving of the threads' lock operations.
+// They only build with the loom cfg (ConcurrentStack then locks a loom Mutex), so run them with:
+//   RUSTFLAGS="--cfg loom" cargo test --release --test loom
+#![cfg(loom)]
+
+use loom::sync::Arc;
+use loom::thread;
+
+use synchronization_thread_safe_stack::ConcurrentStack;
+
+#[test]
+fn concurrent_pushes_are_never_lost() {
+    loom::model(|| {
+        let stack = Arc::new(ConcurrentStack::new());
+        let handles: Vec<_> = (0..2)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || stack.push(t).unwrap())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let mut values = Arc::try_unwrap(stack).ok().unwrap().into_inner().into_vec();
+        values.sort_unstable();
+        assert_eq!(values, vec![0, 1]);
+    });
+}
+
+#[test]
+fn a_racing_push_and_pop_lose_and_duplicate_nothing() {
+    loom::model(|| {
+        let stack = Arc::new(ConcurrentStack::new());
+        stack.push(1).unwrap();
+        let pusher = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.push(2).unwrap())
+        };
+        let popper = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.pop().unwrap())
+        };
+        pusher.join().unwrap();
+        // Whichever order the threads ran in, the pop got one of the two values and the other is left.
+        let popped = popper.join().unwrap().expect("the stack started non-empty");
+        let mut seen = Arc::try_unwrap(stack).ok().unwrap().into_inner().into_vec();
+        seen.push(popped);
+        seen.sort_unstable();
+        assert_eq!(seen, vec![1, 2]);
+    });
+}
@@ -0,0 +1,32 @@
+use synchronization_thread_safe_stack::MinMaxStack;
+
+#[test]
+fn empty_stack_has_no_extremes() {
+    let stack = MinMaxStack::<i32>::new();
+    assert_eq!(stack.min(), None);
+    assert_eq!(stack.max(), None);
+}
+
+#[test]
+fn min_and_max_follow_interleaved_pushes_and_pops() {
+    let mut stack = MinMaxStack::new();
+    stack.push(5);
+    assert_eq!((stack.min(), stack.max()), (Some(&5), Some(&5)));
+    stack.push(3);
+    stack.push(8);
+    assert_eq!((stack.min(), stack.max()), (Some(&3), Some(&8)));
+    stack.push(3);
+    stack.push(1);
+    assert_eq!((stack.min(), stack.max()), (Some(&1), Some(&8)));
+
+    assert_eq!(stack.pop(), Some(1));
+    // The second 3 is still on the stack, so the minimum stays 3 after popping one of them.
+    assert_eq!(stack.pop(), Some(3));
+    assert_eq!((stack.min(), stack.max()), (Some(&3), Some(&8)));
+    assert_eq!(stack.pop(), Some(8));
+    assert_eq!((stack.min(), stack.max()), (Some(&3), Some(&5)));
+    assert_eq!(stack.pop(), Some(3));
+    assert_eq!((stack.min(), stack.max()), (Some(&5), Some(&5)));
+    assert_eq!(stack.pop(), Some(5));
+    assert_eq!((stack.min(), stack.max()), (None, None));
+}
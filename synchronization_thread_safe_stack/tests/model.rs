@@ -0,0 +1,47 @@
+// Property test: random sequences of operations are applied to a Stack and to a Vec used as a model of one
+// (push and pop at the end), and every result has to match. proptest shrinks a failing sequence down to a
+// minimal one before reporting it. The random seed is fixed, so every run tries the same sequences and a
+// failure can be reproduced exactly.
+use proptest::prelude::*;
+use proptest::test_runner::RngSeed;
+use synchronization_thread_safe_stack::Stack;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Push(i32),
+    Pop,
+    Peek,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    // Pushes are weighted up a little so the stack actually grows before the pops catch up.
+    prop_oneof![
+        3 => any::<i32>().prop_map(Op::Push),
+        2 => Just(Op::Pop),
+        1 => Just(Op::Peek),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig { rng_seed: RngSeed::Fixed(0x5eed), cases: 512, ..ProptestConfig::default() })]
+
+    #[test]
+    fn stack_matches_a_vec_model(ops in prop::collection::vec(op(), 0..200)) {
+        let mut stack = Stack::new();
+        let mut model = Vec::new();
+        for op in ops {
+            match op {
+                Op::Push(value) => {
+                    stack.push(value);
+                    model.push(value);
+                }
+                Op::Pop => prop_assert_eq!(stack.pop(), model.pop()),
+                Op::Peek => prop_assert_eq!(stack.peek(), model.last()),
+            }
+            prop_assert_eq!(stack.len(), model.len());
+            prop_assert_eq!(stack.is_empty(), model.is_empty());
+        }
+        // The whole stack, top first, is the model read back to front.
+        prop_assert!(stack.iter().eq(model.iter().rev()));
+    }
+}
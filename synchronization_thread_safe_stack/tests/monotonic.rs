@@ -0,0 +1,38 @@
+use synchronization_thread_safe_stack::MonotonicStack;
+
+#[test]
+fn push_monotonic_evicts_the_smaller_elements() {
+    let mut stack = MonotonicStack::new();
+    assert_eq!(stack.push_monotonic(2), Vec::<i32>::new());
+    assert_eq!(stack.push_monotonic(1), Vec::<i32>::new());
+    // 3 is larger than both, so they are popped, top first.
+    assert_eq!(stack.push_monotonic(3), vec![1, 2]);
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.peek(), Some(&3));
+}
+
+#[test]
+fn equal_elements_are_kept_and_the_stack_stays_sorted() {
+    let mut stack = MonotonicStack::new();
+    for value in [5, 3, 3, 4, 1, 4] {
+        stack.push_monotonic(value);
+        // Top first, every element is no larger than the next one down.
+        assert!(stack.as_stack().to_vec().windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+    assert_eq!(stack.as_stack().to_vec(), vec![4, 4, 5]);
+    assert_eq!(stack.pop(), Some(4));
+}
+
+#[test]
+fn evictions_give_each_elements_next_greater_element() {
+    let values = [2, 7, 3, 5, 4, 6, 8];
+    let mut next_greater = [None; 7];
+    // Track indices, ordered by the value they point at.
+    let mut stack = MonotonicStack::new();
+    for (index, &value) in values.iter().enumerate() {
+        for (_, evicted) in stack.push_monotonic((value, index)) {
+            next_greater[evicted] = Some(value);
+        }
+    }
+    assert_eq!(next_greater, [Some(7), Some(8), Some(5), Some(6), Some(6), Some(8), None]);
+}
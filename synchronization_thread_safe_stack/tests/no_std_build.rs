@@ -0,0 +1,17 @@
+// Build the library with --no-default-features, where it is #![no_std], to make sure nothing outside the "std"
+// feature has picked up a dependency on std. It runs a separate cargo build (with its own target directory,
+// so it doesn't wait on the build running these tests).
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn the_core_builds_without_std() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--no-default-features", "--quiet"])
+        .current_dir(manifest_dir)
+        .env("CARGO_TARGET_DIR", manifest_dir.join("target").join("no_std"))
+        .output()
+        .expect("failed to run cargo");
+    assert!(output.status.success(), "no_std build failed:\n{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,50 @@
+use synchronization_thread_safe_stack::PriorityStack;
+
+#[test]
+fn pop_returns_the_largest_element_first() {
+    let mut stack = PriorityStack::new();
+    for value in [3, 1, 4, 1, 5] {
+        stack.push(value);
+    }
+    assert_eq!(stack.len(), 5);
+    assert_eq!(stack.peek(), Some(&5));
+    let popped: Vec<i32> = std::iter::from_fn(|| stack.pop()).collect();
+    assert_eq!(popped, vec![5, 4, 3, 1, 1]);
+    assert!(stack.is_empty());
+}
+
+// Ordered by priority only, so two jobs with the same priority compare equal.
+struct Job {
+    priority: u8,
+    name: &'static str,
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for Job {}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+#[test]
+fn equal_elements_come_out_newest_first() {
+    let mut stack = PriorityStack::new();
+    stack.push(Job { priority: 1, name: "first" });
+    stack.push(Job { priority: 2, name: "urgent" });
+    stack.push(Job { priority: 1, name: "second" });
+    let names: Vec<&str> = std::iter::from_fn(|| stack.pop()).map(|job| job.name).collect();
+    assert_eq!(names, vec!["urgent", "second", "first"]);
+}
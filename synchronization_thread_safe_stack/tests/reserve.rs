@@ -0,0 +1,65 @@
+// Checks that reserve() really moves the allocations up front, by counting them with a global allocator.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use synchronization_thread_safe_stack::{ArenaStack, Stack};
+
+// Counts the allocations made by the current thread. The count is per thread so tests running in parallel
+// (and the test harness itself) don't add to each other's numbers.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // try_with: the thread-local may already be gone while a thread is shutting down.
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// How many allocations the current thread makes while running 'f'.
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn pushes_after_reserve_on_a_pooled_stack_do_not_allocate() {
+    let mut stack = Stack::with_pool(1000);
+    assert!(allocations_during(|| stack.reserve(1000)) >= 1000);
+    assert_eq!(allocations_during(|| stack.push_iter(0..1000)), 0);
+    assert_eq!(stack.len(), 1000);
+    // The 1001st push has no reserved node left.
+    assert_eq!(allocations_during(|| stack.push(1000)), 1);
+}
+
+#[test]
+fn reserve_is_capped_by_the_pool_size() {
+    let mut stack = Stack::with_pool(10);
+    stack.reserve(1000);
+    assert_eq!(allocations_during(|| stack.push_iter(0..10)), 0);
+    assert_eq!(allocations_during(|| stack.push(10)), 1);
+
+    // Without a pool there is nothing to reserve into.
+    let mut unpooled = Stack::new();
+    assert_eq!(allocations_during(|| unpooled.reserve(1000)), 0);
+    assert_eq!(allocations_during(|| unpooled.push(1)), 1);
+}
+
+#[test]
+fn pushes_after_reserve_on_an_arena_stack_do_not_allocate() {
+    let mut stack = ArenaStack::new();
+    stack.reserve(1000);
+    assert_eq!(allocations_during(|| (0..1000).for_each(|i| stack.push(i))), 0);
+    assert_eq!(stack.len(), 1000);
+}
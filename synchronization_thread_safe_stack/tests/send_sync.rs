@@ -0,0 +1,19 @@
+// Stack<T> has no locking of its own: it is Send when T is Send and Sync when T is Sync, exactly like Vec<T>.
+// Sharing one between threads takes a lock around it (or ConcurrentStack, which has the lock built in).
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use synchronization_thread_safe_stack::Stack;
+
+#[test]
+fn a_stack_behind_arc_mutex_can_be_shared_with_another_thread() {
+    let stack = Arc::new(Mutex::new(Stack::new()));
+    let other = Arc::clone(&stack);
+    thread::spawn(move || other.lock().unwrap().push(1)).join().unwrap();
+    assert_eq!(stack.lock().unwrap().pop(), Some(1));
+}
+
+#[test]
+fn a_stack_of_non_send_elements_cannot_cross_threads() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,13 @@
+#![cfg(feature = "serde")]
+
+use synchronization_thread_safe_stack::Stack;
+
+#[test]
+fn json_round_trip_preserves_order() {
+    let stack: Stack<i32> = (1..=3).collect();
+    let json = serde_json::to_string(&stack).unwrap();
+    assert_eq!(json, "[3,2,1]");
+    let restored: Stack<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.peek(), Some(&3));
+    assert_eq!(restored, stack);
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "std")]
+
+use std::thread;
+
+use synchronization_thread_safe_stack::ShardedStack;
+
+#[test]
+fn push_and_pop_on_one_thread() {
+    let stack = ShardedStack::<i32, 4>::new();
+    assert_eq!(stack.pop().unwrap(), None);
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.len().unwrap(), 2);
+    // One thread always pushes to the same shard, so on its own it still sees LIFO order.
+    assert_eq!(stack.pop().unwrap(), Some(2));
+    assert_eq!(stack.pop().unwrap(), Some(1));
+    assert!(stack.is_empty().unwrap());
+}
+
+#[test]
+fn pushes_from_16_threads_are_all_popped_or_left_on_the_stack() {
+    const THREADS: u64 = 16;
+    const ITERATIONS: u64 = 5_000;
+    let stack = ShardedStack::<u64, 8>::new();
+    let (pushed, popped) = thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let stack = &stack;
+                scope.spawn(move || {
+                    let (mut pushed, mut popped) = (0, 0);
+                    for i in 0..ITERATIONS {
+                        stack.push(t * ITERATIONS + i).unwrap();
+                        pushed += 1;
+                        // Pop on every other iteration so elements build up and pops have to look across shards.
+                        if i % 2 == 0 && stack.pop().unwrap().is_some() {
+                            popped += 1;
+                        }
+                    }
+                    (pushed, popped)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .fold((0, 0), |(pushed, popped), (p, q)| (pushed + p, popped + q))
+    });
+    let remaining = stack.len().unwrap() as u64;
+    assert_eq!(pushed, THREADS * ITERATIONS);
+    assert_eq!(pushed, popped + remaining);
+
+    let mut left = Vec::new();
+    while let Some(value) = stack.pop().unwrap() {
+        left.push(value);
+    }
+    assert_eq!(left.len() as u64, remaining);
+}
@@ -0,0 +1,774 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use synchronization_thread_safe_stack::{Stack, StackEvent};
+
+#[test]
+fn pop_returns_values_in_lifo_order() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.pop(), Some(3));
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.pop(), Some(1));
+    assert_eq!(stack.pop(), None);
+}
+
+#[test]
+fn dropping_a_deep_stack_does_not_overflow() {
+    let mut stack = Stack::new();
+    for i in 0..1_000_000 {
+        stack.push(i);
+    }
+    drop(stack);
+}
+
+#[test]
+fn collecting_cloning_and_dropping_a_very_deep_stack_does_not_overflow() {
+    // Deep enough that any recursive walk over the nodes would overflow a test thread's stack.
+    const DEPTH: usize = 2_000_000;
+    let stack: Stack<usize> = (0..DEPTH).collect();
+    assert_eq!(stack.len(), DEPTH);
+    let mut copy = stack.clone();
+    assert_eq!(copy, stack);
+    copy.reverse();
+    assert_eq!(copy.peek(), Some(&0));
+    drop(copy);
+    drop(stack);
+}
+
+#[test]
+fn peek_on_empty_stack_returns_none() {
+    let mut stack = Stack::<i32>::new();
+    assert_eq!(stack.peek(), None);
+    assert_eq!(stack.peek_mut(), None);
+}
+
+#[test]
+fn peek_then_pop_returns_the_same_value() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    assert_eq!(stack.peek(), Some(&2));
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.peek(), Some(&1));
+}
+
+#[test]
+fn peek_mut_modifies_the_top_value() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    if let Some(top) = stack.peek_mut() {
+        *top = 10;
+    }
+    assert_eq!(stack.pop(), Some(10));
+}
+
+#[test]
+fn len_tracks_interleaved_push_and_pop() {
+    let mut stack = Stack::new();
+    assert!(stack.is_empty());
+    for i in 0..500 {
+        stack.push(i);
+    }
+    for _ in 0..300 {
+        stack.pop();
+    }
+    assert_eq!(stack.len(), 200);
+    assert!(!stack.is_empty());
+}
+
+#[test]
+fn into_iter_yields_elements_in_lifo_order() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    let values: Vec<i32> = stack.into_iter().collect();
+    assert_eq!(values, vec![3, 2, 1]);
+}
+
+#[test]
+fn iter_walks_from_top_to_bottom_without_consuming() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    let values: Vec<&i32> = stack.iter().collect();
+    assert_eq!(values, vec![&3, &2, &1]);
+    assert_eq!(stack.iter().count(), stack.len());
+    assert_eq!(Stack::<i32>::new().iter().next(), None);
+}
+
+#[test]
+fn collect_puts_the_last_item_on_top() {
+    let mut stack: Stack<i32> = (0..5).collect();
+    assert_eq!(stack.peek(), Some(&4));
+    assert_eq!(stack.len(), 5);
+    stack.extend(vec![5, 6, 7]);
+    assert_eq!(stack.peek(), Some(&7));
+    assert_eq!(stack.len(), 8);
+}
+
+#[test]
+fn debug_and_display_print_top_to_bottom() {
+    let mut stack = Stack::new();
+    assert_eq!(format!("{:?}", stack), "[]");
+    assert_eq!(format!("{}", stack), "[]");
+    stack.push("a");
+    stack.push("b");
+    stack.push("c");
+    assert_eq!(format!("{:?}", stack), r#"["c", "b", "a"]"#);
+    assert_eq!(format!("{}", stack), "[c, b, a]");
+    assert_eq!(stack.len(), 3);
+}
+
+#[test]
+fn clear_empties_the_stack() {
+    let mut stack: Stack<i32> = (0..100).collect();
+    stack.clear();
+    assert!(stack.is_empty());
+    assert_eq!(stack.len(), 0);
+    assert_eq!(stack.pop(), None);
+}
+
+#[test]
+fn to_vec_and_into_vec_list_elements_top_first() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.into_vec(), vec![3, 2, 1]);
+}
+
+#[test]
+fn clone_is_independent_of_the_original() {
+    let mut original: Stack<i32> = (1..=3).collect();
+    let copy = original.clone();
+    assert_eq!(original.pop(), Some(3));
+    assert_eq!(copy.to_vec(), vec![3, 2, 1]);
+    assert_eq!(copy.len(), 3);
+}
+
+#[test]
+fn stacks_with_the_same_elements_are_equal() {
+    let a: Stack<i32> = (0..5).collect();
+    let b: Stack<i32> = (0..5).collect();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn stacks_with_different_lengths_are_not_equal() {
+    let a: Stack<i32> = (0..5).collect();
+    let b: Stack<i32> = (0..4).collect();
+    assert_ne!(a, b);
+}
+
+#[test]
+fn stacks_with_different_contents_are_not_equal() {
+    let a: Stack<i32> = (0..5).collect();
+    let b: Stack<i32> = (1..6).collect();
+    assert_ne!(a, b);
+}
+
+#[test]
+fn default_stack_is_empty() {
+    assert!(Stack::<i32>::default().is_empty());
+}
+
+#[test]
+fn pop_n_returns_everything_when_n_exceeds_len() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.pop_n(10), vec![3, 2, 1]);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn pop_n_with_zero_returns_nothing() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.pop_n(0), Vec::<i32>::new());
+    assert_eq!(stack.len(), 3);
+}
+
+#[test]
+fn push_iter_pushes_a_range_in_order() {
+    let mut stack = Stack::new();
+    stack.push_iter(0..10);
+    assert_eq!(stack.len(), 10);
+    assert_eq!(stack.peek(), Some(&9));
+}
+
+#[test]
+fn reverse_turns_the_bottom_into_the_top() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+    stack.reverse();
+    assert_eq!(stack.to_vec(), vec![1, 2, 3]);
+    assert_eq!(stack.len(), 3);
+
+    let mut empty = Stack::<i32>::new();
+    empty.reverse();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn contains_finds_present_values_only() {
+    let stack: Stack<i32> = (0..5).collect();
+    assert!(stack.contains(&0));
+    assert!(stack.contains(&4));
+    assert!(!stack.contains(&5));
+    assert!(!Stack::<i32>::new().contains(&0));
+}
+
+#[test]
+fn drain_filter_removes_matching_elements_and_keeps_order() {
+    let mut stack: Stack<i32> = (0..10).collect();
+    let removed = stack.drain_filter(|value| value % 2 == 0);
+    assert_eq!(removed, vec![8, 6, 4, 2, 0]);
+    assert_eq!(stack.to_vec(), vec![9, 7, 5, 3, 1]);
+    assert_eq!(stack.len(), 5);
+}
+
+#[test]
+fn into_sorted_vec_sorts_a_shuffled_range() {
+    // 7919 is coprime with 100, so this visits every value in 0..100 exactly once, out of order.
+    let stack: Stack<i32> = (0..100).map(|i| (i * 7919) % 100).collect();
+    assert_eq!(stack.into_sorted_vec(), (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn pop_if_pops_when_the_predicate_holds() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.pop_if(|top| *top == 3), Some(3));
+    assert_eq!(stack.len(), 2);
+}
+
+#[test]
+fn pop_if_leaves_the_stack_alone_when_the_predicate_fails() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.pop_if(|top| *top > 3), None);
+    assert_eq!(stack.peek(), Some(&3));
+    assert_eq!(stack.len(), 3);
+    assert_eq!(Stack::<i32>::new().pop_if(|_| true), None);
+}
+
+#[test]
+fn peek_n_returns_the_top_elements_top_first() {
+    let stack: Stack<i32> = (3..=5).collect();
+    assert_eq!(stack.peek_n(2), vec![&5, &4]);
+    assert_eq!(stack.peek_n(10), vec![&5, &4, &3]);
+    assert_eq!(stack.len(), 3);
+}
+
+#[test]
+fn equal_stacks_collapse_in_a_hash_set() {
+    let mut set = std::collections::HashSet::new();
+    set.insert((0..5).collect::<Stack<i32>>());
+    set.insert((0..5).collect::<Stack<i32>>());
+    assert_eq!(set.len(), 1);
+    set.insert((0..4).collect::<Stack<i32>>());
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn append_moves_other_on_top() {
+    let mut stack: Stack<i32> = (4..=6).collect();
+    let mut other: Stack<i32> = (1..=3).collect();
+    stack.append(&mut other);
+    assert_eq!(stack.to_vec(), vec![3, 2, 1, 6, 5, 4]);
+    assert_eq!(stack.len(), 6);
+    assert!(other.is_empty());
+    assert_eq!(other.len(), 0);
+
+    // Appending an empty stack changes nothing.
+    stack.append(&mut other);
+    assert_eq!(stack.len(), 6);
+}
+
+#[test]
+fn retain_keeps_matching_elements_in_order() {
+    let mut stack: Stack<i32> = (0..10).collect();
+    stack.retain(|value| value % 2 == 1);
+    assert_eq!(stack.to_vec(), vec![9, 7, 5, 3, 1]);
+    assert_eq!(stack.len(), 5);
+}
+
+#[test]
+fn swap_top_swaps_the_two_topmost_elements() {
+    let mut stack: Stack<i32> = (1..=2).collect();
+    stack.swap_top();
+    assert_eq!(stack.to_vec(), vec![1, 2]);
+}
+
+#[test]
+fn swap_top_on_one_element_is_a_no_op() {
+    let mut stack: Stack<i32> = (1..=1).collect();
+    stack.swap_top();
+    assert_eq!(stack.to_vec(), vec![1]);
+}
+
+#[test]
+fn swap_top_on_an_empty_stack_is_a_no_op() {
+    let mut stack = Stack::<i32>::new();
+    stack.swap_top();
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn pooled_stack_behaves_like_a_plain_stack() {
+    let mut pooled = Stack::with_pool(4);
+    let mut plain = Stack::new();
+    for round in 0..10 {
+        for i in 0..round {
+            pooled.push(i.to_string());
+            plain.push(i.to_string());
+        }
+        for _ in 0..round / 2 + 1 {
+            assert_eq!(pooled.pop(), plain.pop());
+        }
+        assert_eq!(pooled, plain);
+        assert_eq!(pooled.len(), plain.len());
+    }
+}
+
+#[test]
+fn dropping_a_partially_consumed_drain_keeps_the_rest() {
+    let mut stack: Stack<i32> = (1..=5).collect();
+    let drained: Vec<i32> = stack.drain().take(2).collect();
+    assert_eq!(drained, vec![5, 4]);
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+}
+
+#[test]
+fn split_off_at_zero_moves_everything() {
+    let mut stack: Stack<i32> = (1..=5).collect();
+    let bottom = stack.split_off(0);
+    assert!(stack.is_empty());
+    assert_eq!(bottom.to_vec(), vec![5, 4, 3, 2, 1]);
+    assert_eq!(bottom.len(), 5);
+}
+
+#[test]
+fn split_off_at_len_moves_nothing() {
+    let mut stack: Stack<i32> = (1..=5).collect();
+    let bottom = stack.split_off(5);
+    assert_eq!(stack.to_vec(), vec![5, 4, 3, 2, 1]);
+    assert_eq!(stack.len(), 5);
+    assert!(bottom.is_empty());
+    assert_eq!(bottom.len(), 0);
+}
+
+#[test]
+fn split_off_in_the_middle_divides_the_stack() {
+    let mut stack: Stack<i32> = (1..=5).collect();
+    let bottom = stack.split_off(2);
+    assert_eq!(stack.to_vec(), vec![5, 4]);
+    assert_eq!(stack.len(), 2);
+    assert_eq!(bottom.to_vec(), vec![3, 2, 1]);
+    assert_eq!(bottom.len(), 3);
+}
+
+#[test]
+fn get_indexes_from_the_top() {
+    let stack: Stack<i32> = (1..=5).collect();
+    assert_eq!(stack.get(0), Some(&5));
+    assert_eq!(stack.get(2), Some(&3));
+    assert_eq!(stack.get(5), None);
+}
+
+#[test]
+fn memory_bytes_grows_linearly_with_len() {
+    let mut stack = Stack::new();
+    assert_eq!(stack.memory_bytes(), 0);
+    stack.push(1u64);
+    let per_node = stack.memory_bytes();
+    assert!(per_node >= std::mem::size_of::<u64>());
+    stack.extend(2..=100);
+    assert_eq!(stack.memory_bytes(), 100 * per_node);
+    stack.pop_n(40);
+    assert_eq!(stack.memory_bytes(), 60 * per_node);
+}
+
+#[test]
+fn memory_bytes_counts_pooled_allocations() {
+    let mut stack = Stack::with_pool(8);
+    stack.extend(0..10u64);
+    let full = stack.memory_bytes();
+    stack.pop_n(10);
+    // Eight of the popped nodes stay allocated in the pool.
+    assert_eq!(stack.memory_bytes(), full / 10 * 8);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn to_multiset_counts_duplicates_regardless_of_order() {
+    let stack: Stack<&str> = ["a", "b", "a", "c", "a", "b"].into_iter().collect();
+    let counts = stack.to_multiset();
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts["a"], 3);
+    assert_eq!(counts["b"], 2);
+    assert_eq!(counts["c"], 1);
+
+    let reordered: Stack<&str> = ["b", "a", "c", "b", "a", "a"].into_iter().collect();
+    assert_ne!(stack, reordered);
+    assert_eq!(stack.to_multiset(), reordered.to_multiset());
+    assert!(Stack::<i32>::new().to_multiset().is_empty());
+}
+
+#[test]
+fn push_scoped_pops_the_value_when_the_guard_drops() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    {
+        let guard = stack.push_scoped(2);
+        assert_eq!(guard.peek(), Some(&2));
+        assert_eq!(guard.len(), 2);
+    }
+    assert_eq!(stack.to_vec(), vec![1]);
+}
+
+#[test]
+fn a_committed_push_guard_keeps_its_value() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push_scoped(2).commit();
+    assert_eq!(stack.to_vec(), vec![2, 1]);
+}
+
+#[test]
+fn push_scoped_pops_on_an_early_return() {
+    fn visit(stack: &mut Stack<&'static str>, fail: bool) -> Result<usize, ()> {
+        let guard = stack.push_scoped("visit");
+        if fail {
+            return Err(());
+        }
+        Ok(guard.len())
+    }
+    let mut stack = Stack::new();
+    assert_eq!(visit(&mut stack, false), Ok(1));
+    assert_eq!(visit(&mut stack, true), Err(()));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn rotate_brings_the_kth_element_to_the_top() {
+    // Pushed in order, so 5 is on top.
+    let mut stack: Stack<i32> = (1..=5).collect();
+    stack.rotate(3);
+    assert_eq!(stack.to_vec(), vec![3, 5, 4, 2, 1]);
+    stack.rotate(5);
+    assert_eq!(stack.to_vec(), vec![1, 3, 5, 4, 2]);
+    stack.rotate(2);
+    assert_eq!(stack.to_vec(), vec![3, 1, 5, 4, 2]);
+    assert_eq!(stack.len(), 5);
+}
+
+#[test]
+fn rotate_beyond_len_or_below_two_does_nothing() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    stack.rotate(4);
+    stack.rotate(1);
+    stack.rotate(0);
+    assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+    let mut empty = Stack::<i32>::new();
+    empty.rotate(2);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn from_vec_puts_the_last_element_on_top() {
+    let stack = Stack::from(vec![1, 2, 3]);
+    assert_eq!(stack.peek(), Some(&3));
+    assert_eq!(stack.len(), 3);
+}
+
+#[test]
+fn converting_from_a_vec_and_back_reverses_it() {
+    let original = vec!["a", "b", "c"];
+    let stack: Stack<&str> = original.clone().into();
+    let back: Vec<&str> = stack.into();
+    assert_eq!(back, vec!["c", "b", "a"]);
+    // Going through the stack twice restores the original order.
+    let twice: Vec<&str> = Stack::from(back).into();
+    assert_eq!(twice, original);
+}
+
+#[test]
+fn checked_push_rejects_a_push_past_usize_max() {
+    let mut stack = Stack::with_forced_len(usize::MAX);
+    assert_eq!(stack.checked_push(1), Err(1));
+    assert_eq!(stack.len(), usize::MAX);
+
+    let mut stack = Stack::with_forced_len(usize::MAX - 1);
+    assert_eq!(stack.checked_push(1), Ok(()));
+    assert_eq!(stack.len(), usize::MAX);
+    assert_eq!(stack.checked_push(2), Err(2));
+}
+
+#[test]
+#[should_panic(expected = "overflowed")]
+fn push_past_usize_max_panics() {
+    let mut stack = Stack::with_forced_len(usize::MAX);
+    stack.push(1);
+}
+
+#[test]
+fn checked_push_works_like_push_on_a_normal_stack() {
+    let mut stack = Stack::new();
+    assert_eq!(stack.checked_push(1), Ok(()));
+    assert_eq!(stack.checked_push(2), Ok(()));
+    assert_eq!(stack.to_vec(), vec![2, 1]);
+}
+
+#[test]
+fn fold_sums_the_elements() {
+    let stack: Stack<i32> = (1..=5).collect();
+    assert_eq!(stack.fold(0, |sum, n| sum + n), 15);
+}
+
+#[test]
+fn fold_visits_elements_top_first() {
+    let stack: Stack<&str> = ["a", "b", "c"].into_iter().collect();
+    assert_eq!(stack.fold(String::new(), |acc, s| acc + s), "cba");
+    assert_eq!(Stack::<i32>::new().fold(7, |acc, n| acc + n), 7);
+}
+
+#[test]
+fn map_transforms_elements_and_keeps_their_order() {
+    let stack: Stack<i32> = (1..=3).collect();
+    let strings: Stack<String> = stack.map(|n| n.to_string());
+    assert_eq!(strings.peek(), Some(&"3".to_string()));
+    assert_eq!(strings.len(), 3);
+    assert_eq!(strings.to_vec(), vec!["3", "2", "1"]);
+}
+
+#[test]
+fn map_calls_the_closure_top_first() {
+    let stack: Stack<i32> = (1..=3).collect();
+    let mut seen = Vec::new();
+    let doubled = stack.map(|n| {
+        seen.push(n);
+        n * 2
+    });
+    assert_eq!(seen, vec![3, 2, 1]);
+    assert_eq!(doubled.to_vec(), vec![6, 4, 2]);
+}
+
+#[test]
+fn dedup_consecutive_collapses_runs_anywhere_in_the_stack() {
+    // Listed top first: a run at the top, one in the middle and one at the bottom.
+    let top_first = [1, 1, 1, 2, 3, 3, 2, 4, 4];
+    let mut stack: Stack<i32> = top_first.iter().rev().copied().collect();
+    stack.dedup_consecutive();
+    assert_eq!(stack.to_vec(), vec![1, 2, 3, 2, 4]);
+    assert_eq!(stack.len(), 5);
+}
+
+#[test]
+fn dedup_consecutive_leaves_non_adjacent_duplicates_and_edge_cases_alone() {
+    let mut stack: Stack<i32> = [1, 2, 1, 2].into_iter().collect();
+    stack.dedup_consecutive();
+    assert_eq!(stack.len(), 4);
+
+    let mut same: Stack<i32> = [7; 10].into_iter().collect();
+    same.dedup_consecutive();
+    assert_eq!(same.to_vec(), vec![7]);
+
+    let mut empty = Stack::<i32>::new();
+    empty.dedup_consecutive();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn into_iter_reports_its_exact_remaining_length() {
+    let stack: Stack<i32> = (1..=4).collect();
+    let mut iter = stack.into_iter();
+    assert_eq!(iter.len(), 4);
+    iter.next();
+    assert_eq!(iter.len(), 3);
+    iter.by_ref().take(3).for_each(drop);
+    assert_eq!(iter.len(), 0);
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn iter_and_drain_report_their_exact_remaining_length() {
+    let mut stack: Stack<i32> = (1..=4).collect();
+    let mut iter = stack.iter();
+    assert_eq!(iter.len(), 4);
+    iter.next();
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+
+    let mut drain = stack.drain();
+    drain.next();
+    assert_eq!(drain.len(), 3);
+}
+
+#[test]
+fn from_fn_pushes_each_index_in_order() {
+    let stack = Stack::from_fn(5, |i| i * 2);
+    assert_eq!(stack.len(), 5);
+    // f(4) was pushed last, so it is on top.
+    assert_eq!(stack.to_vec(), vec![8, 6, 4, 2, 0]);
+    assert!(Stack::from_fn(0, |i| i).is_empty());
+}
+
+#[test]
+fn on_event_sees_every_push_and_pop() {
+    let pushes = Arc::new(AtomicUsize::new(0));
+    let pops = Arc::new(AtomicUsize::new(0));
+    let mut stack = Stack::new();
+    let (push_count, pop_count) = (Arc::clone(&pushes), Arc::clone(&pops));
+    stack.on_event(move |event| {
+        let counter = match event {
+            StackEvent::Push(_) => &push_count,
+            StackEvent::Pop(_) => &pop_count,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    stack.push_iter(0..5);
+    stack.pop();
+    stack.pop_n(2);
+    // clear() drops the other two without popping them, and popping the now empty stack isn't an event.
+    stack.clear();
+    stack.pop();
+    assert_eq!(pushes.load(Ordering::Relaxed), 5);
+    assert_eq!(pops.load(Ordering::Relaxed), 3);
+
+    stack.clear_on_event();
+    stack.push(1);
+    assert_eq!(pushes.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn on_event_is_given_the_element_pushed_or_popped() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut stack = Stack::new();
+    let log = Arc::clone(&seen);
+    stack.on_event(move |event| log.lock().unwrap().push(match event {
+        StackEvent::Push(data) => StackEvent::Push(*data),
+        StackEvent::Pop(data) => StackEvent::Pop(*data),
+    }));
+    stack.push(1);
+    stack.push(2);
+    stack.pop();
+    assert_eq!(*seen.lock().unwrap(), vec![StackEvent::Push(1), StackEvent::Push(2), StackEvent::Pop(2)]);
+}
+
+#[test]
+fn into_fifo_vec_returns_elements_in_push_order() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.into_fifo_vec(), vec![1, 2, 3]);
+    assert!(Stack::<i32>::new().into_fifo_vec().is_empty());
+}
+
+#[test]
+fn with_initial_puts_the_last_slice_element_on_top() {
+    let items = ["a".to_string(), "b".to_string(), "c".to_string()];
+    let stack = Stack::with_initial(&items);
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.peek(), items.last());
+    assert_eq!(stack.to_vec(), vec!["c", "b", "a"]);
+}
+
+#[test]
+fn is_sorted_descending_accepts_a_stack_with_the_largest_on_top() {
+    // Pushing in ascending order leaves the largest value on top; equal neighbours are fine.
+    let stack: Stack<i32> = vec![1, 2, 2, 5].into();
+    assert!(stack.is_sorted_descending());
+    assert!(Stack::<i32>::new().is_sorted_descending());
+}
+
+#[test]
+fn is_sorted_descending_rejects_an_unsorted_stack() {
+    let stack: Stack<i32> = vec![1, 3, 2].into();
+    assert!(!stack.is_sorted_descending());
+    let floats: Stack<f64> = vec![1.0, f64::NAN, 3.0].into();
+    assert!(!floats.is_sorted_descending());
+}
+
+#[test]
+fn a_single_element_stack_is_sorted() {
+    let stack: Stack<i32> = vec![7].into();
+    assert!(stack.is_sorted_descending());
+}
+
+#[test]
+fn pop_with_len_returns_the_length_left_behind() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.pop_with_len(), Some((3, 2)));
+    assert_eq!(stack.pop_with_len(), Some((2, 1)));
+    assert_eq!(stack.pop_with_len(), Some((1, 0)));
+    assert_eq!(stack.pop_with_len(), None);
+}
+
+#[test]
+fn partition_splits_evens_from_odds_keeping_their_order() {
+    let stack: Stack<i32> = (0..10).collect();
+    let (evens, odds) = stack.partition(|n| n % 2 == 0);
+    assert_eq!(evens.to_vec(), vec![8, 6, 4, 2, 0]);
+    assert_eq!(odds.to_vec(), vec![9, 7, 5, 3, 1]);
+    assert_eq!(evens.peek(), Some(&8));
+}
+
+#[test]
+fn count_where_counts_matches_and_leaves_the_stack_alone() {
+    let stack: Stack<i32> = (0..10).collect();
+    assert_eq!(stack.count_where(|n| n % 2 == 0), 5);
+    assert_eq!(stack.count_where(|n| *n > 100), 0);
+    assert_eq!(stack.len(), 10);
+}
+
+#[test]
+fn drain_chunks_pops_in_batches_of_the_chunk_size() {
+    let mut stack: Stack<i32> = (0..1000).collect();
+    let mut chunks = stack.drain_chunks(256);
+    assert_eq!(chunks.len(), 4);
+    let chunks: Vec<Vec<i32>> = chunks.by_ref().collect();
+    let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+    assert_eq!(sizes, vec![256, 256, 256, 232]);
+    assert_eq!(chunks.iter().map(Vec::len).sum::<usize>(), 1000);
+    // Each chunk comes off the top, so the first one starts with the last element pushed.
+    assert_eq!(chunks[0][0], 999);
+    assert_eq!(chunks[3].last(), Some(&0));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn drain_chunks_leaves_whatever_it_did_not_reach() {
+    let mut stack: Stack<i32> = (0..10).collect();
+    assert_eq!(stack.drain_chunks(3).next(), Some(vec![9, 8, 7]));
+    assert_eq!(stack.len(), 7);
+}
+
+#[test]
+fn replace_top_swaps_the_top_element_in_place() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+    assert_eq!(stack.replace_top(30), Some(3));
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.into_vec(), vec![30, 2, 1]);
+}
+
+#[test]
+fn replace_top_on_an_empty_stack_pushes_nothing() {
+    let mut stack = Stack::new();
+    assert_eq!(stack.replace_top(1), None);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn replace_top_or_push_pushes_onto_an_empty_stack() {
+    let mut stack = Stack::new();
+    assert_eq!(stack.replace_top_or_push(1), None);
+    assert_eq!(stack.replace_top_or_push(2), Some(1));
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.peek(), Some(&2));
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "tracing")]
+
+use std::io;
+
+use synchronization_thread_safe_stack::demo::{pop_and_log, test_stack, thread_number, LogFormat};
+use synchronization_thread_safe_stack::ConcurrentStack;
+use tracing_test::traced_test;
+
+#[traced_test]
+#[test]
+fn every_push_and_pop_is_reported_as_a_debug_event() {
+    let stack = ConcurrentStack::new();
+    // The events replace the log, so the log itself can go nowhere.
+    test_stack(&stack, &mut io::sink(), 1, LogFormat::Text).unwrap();
+    let thread = format!("thread={}", thread_number());
+    logs_assert(|lines: &[&str]| {
+        let events: Vec<&&str> = lines.iter().filter(|line| line.contains("DEBUG")).collect();
+        // One iteration pushes 1, 2 and 3 and pops three times.
+        if events.len() != 6 {
+            return Err(format!("expected 6 events, got {}", events.len()));
+        }
+        match events.iter().find(|line| !line.contains(&thread)) {
+            Some(line) => Err(format!("event without {}: {}", thread, line)),
+            None => Ok(()),
+        }
+    });
+    assert!(logs_contain("push thread="));
+    assert!(logs_contain("value=3"));
+    assert!(logs_contain("pop thread="));
+}
+
+#[traced_test]
+#[test]
+fn an_empty_pop_is_reported_too() {
+    let stack = ConcurrentStack::<i32>::new();
+    pop_and_log(&stack, &mut io::sink(), LogFormat::Text).unwrap();
+    assert!(logs_contain("pop from an empty stack"));
+}
@@ -0,0 +1,13 @@
+// Rc's reference count isn't atomic, so a Stack holding Rcs must not be moved to another thread.
+use std::rc::Rc;
+use std::thread;
+
+use synchronization_thread_safe_stack::Stack;
+
+fn main() {
+    let mut stack = Stack::new();
+    stack.push(Rc::new(1));
+    thread::spawn(move || {
+        stack.pop();
+    });
+}